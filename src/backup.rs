@@ -0,0 +1,235 @@
+use std::{
+    collections::HashSet,
+    io::{Read, Write},
+    str::FromStr,
+};
+
+use chrono::{Local, NaiveDateTime};
+use eyre::{eyre, Context};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    day::{Day, DayKind, DayRepository},
+    task::{Task, TaskRepository},
+};
+
+// increase when the document changes incompatibly, older releases refuse newer backups
+const BACKUP_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub struct Backup {
+    version: u32,
+    created: NaiveDateTime,
+    days: Vec<BackupDay>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BackupDay {
+    #[serde(flatten)]
+    day: Day,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    #[serde(default)]
+    tasks: Vec<Task<u64>>,
+}
+
+pub enum RestoreMode {
+    Merge,
+    Replace,
+}
+
+#[derive(Default)]
+pub struct Restored {
+    days: usize,
+    tasks: usize,
+    duplicates: usize,
+    skipped: Vec<String>,
+}
+
+impl FromStr for RestoreMode {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "merge" => Ok(Self::Merge),
+            "replace" => Ok(Self::Replace),
+            _ => Err(eyre!("the restore mode is not supported: {}", s)),
+        }
+    }
+}
+
+impl Backup {
+    pub fn create(days: &DayRepository, tasks: &TaskRepository) -> eyre::Result<Self> {
+        let mut tasks = tasks
+            .all()?
+            .into_iter()
+            .into_group_map_by(|task| task.day());
+        let days = days
+            .all()?
+            .into_iter()
+            .map(|day| {
+                Ok(BackupDay {
+                    note: days.note(&day)?,
+                    tasks: tasks.remove(&day.id()).unwrap_or_default(),
+                    day,
+                })
+            })
+            .collect::<eyre::Result<_>>()?;
+        Ok(Self {
+            version: BACKUP_VERSION,
+            created: Local::now().naive_local(),
+            days,
+        })
+    }
+
+    pub fn read(reader: impl Read) -> eyre::Result<Self> {
+        let backup: Self = serde_json::from_reader(reader).wrap_err("could not read the backup")?;
+        if backup.version > BACKUP_VERSION {
+            return Err(eyre!(
+                "the backup has the version {}, but only version {} is supported. please update ttrace.",
+                backup.version,
+                BACKUP_VERSION
+            ));
+        }
+        let mut dates = HashSet::new();
+        if let Some(day) = backup.days.iter().find(|day| !dates.insert(day.day.date())) {
+            return Err(eyre!(
+                "the backup contains the day twice: {}",
+                day.day.date()
+            ));
+        }
+        Ok(backup)
+    }
+
+    pub fn write(&self, writer: impl Write) -> eyre::Result<()> {
+        serde_json::to_writer_pretty(writer, self).wrap_err("could not write the backup")
+    }
+
+    pub fn restore(
+        self,
+        days: &DayRepository,
+        tasks: &TaskRepository,
+        mode: RestoreMode,
+    ) -> eyre::Result<Restored> {
+        let transaction = tasks.transaction()?;
+        let restored = match mode {
+            RestoreMode::Merge => self.merge(days, tasks)?,
+            RestoreMode::Replace => self.replace(days, tasks)?,
+        };
+        transaction
+            .commit()
+            .wrap_err("could not commit the restored backup")?;
+        Ok(restored)
+    }
+
+    fn replace(self, days: &DayRepository, tasks: &TaskRepository) -> eyre::Result<Restored> {
+        tasks.clear()?;
+        days.clear()?;
+        let mut restored = Restored::default();
+        for backup_day in self.days {
+            let day = days.restore(backup_day.day, backup_day.note.as_deref())?;
+            restored.days += 1;
+            for task in backup_day.tasks {
+                tasks
+                    .restore_task(normalize(task, day))
+                    .wrap_err("could not restore the task")?;
+                restored.tasks += 1;
+            }
+        }
+        Ok(restored)
+    }
+
+    fn merge(self, days: &DayRepository, tasks: &TaskRepository) -> eyre::Result<Restored> {
+        let mut restored = Restored::default();
+        for backup_day in self.days {
+            let mut day = days.lookup(backup_day.day.date())?;
+            if day.kind() == DayKind::Workday && backup_day.day.kind() != DayKind::Workday {
+                day = days.set_kind(day, backup_day.day.kind())?;
+            }
+            if let Some(note) = backup_day.note.as_deref() {
+                if days.note(&day)?.is_none() {
+                    day = days.set_note(day, Some(note))?;
+                }
+            }
+            restored.days += 1;
+            for task in backup_day.tasks {
+                let task = normalize(task, day);
+                let existing = tasks.day_with_tasks(day)?;
+                if existing
+                    .tasks()
+                    .any(|existing| is_duplicate(existing, &task))
+                {
+                    restored.duplicates += 1;
+                    continue;
+                }
+                let added = match task.end() {
+                    Some(end) => tasks.add(
+                        day,
+                        task.description(),
+                        task.tags(),
+                        task.start(),
+                        end,
+                        false,
+                    ),
+                    None => tasks.start_at(day, task.description(), task.tags(), task.start()),
+                };
+                let added = match added {
+                    Ok(added) if !task.is_billable() => tasks.set_billable(added, false),
+                    added => added,
+                };
+                match added {
+                    Ok(added) => {
+                        day = added.day();
+                        restored.tasks += 1;
+                    }
+                    Err(error) => restored.skipped.push(format!(
+                        "{} {} {}: {}",
+                        day.date(),
+                        task.start().format("%H:%M"),
+                        task.description(),
+                        error.root_cause()
+                    )),
+                }
+            }
+        }
+        Ok(restored)
+    }
+}
+
+impl Restored {
+    pub fn days(&self) -> usize {
+        self.days
+    }
+
+    pub fn tasks(&self) -> usize {
+        self.tasks
+    }
+
+    pub fn duplicates(&self) -> usize {
+        self.duplicates
+    }
+
+    pub fn skipped(&self) -> &[String] {
+        self.skipped.as_slice()
+    }
+}
+
+fn normalize(task: Task<u64>, day: Day) -> Task<u64> {
+    Task::new(
+        task.id(),
+        day.id(),
+        task.start(),
+        task.end(),
+        task.description().to_owned(),
+        task.tags().to_vec(),
+    )
+    .with_billable(task.is_billable())
+}
+
+fn is_duplicate(existing: &Task<Day>, task: &Task<u64>) -> bool {
+    existing.start() == task.start()
+        && existing.end() == task.end()
+        && existing.description() == task.description()
+        && existing.tags() == task.tags()
+}
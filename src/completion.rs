@@ -0,0 +1,136 @@
+use std::str::FromStr;
+
+use clap::Command;
+use eyre::eyre;
+
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for Shell {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "fish" => Ok(Self::Fish),
+            _ => Err(eyre!("the shell is not supported: {}", s)),
+        }
+    }
+}
+
+pub fn script(shell: Shell, command: &Command) -> String {
+    let commands: Vec<_> = command
+        .get_subcommands()
+        .filter(|command| !command.is_hide_set())
+        .map(|command| {
+            let about = command
+                .get_about()
+                .map(|about| about.to_string())
+                .unwrap_or_default();
+            (command.get_name(), about)
+        })
+        .collect();
+    match shell {
+        Shell::Bash => bash(&commands),
+        Shell::Zsh => zsh(&commands),
+        Shell::Fish => fish(&commands),
+    }
+}
+
+fn bash(commands: &[(&str, String)]) -> String {
+    let names: Vec<_> = commands.iter().map(|(name, _)| *name).collect();
+    BASH.replace("{commands}", &names.join("\n"))
+}
+
+fn zsh(commands: &[(&str, String)]) -> String {
+    let commands: Vec<_> = commands
+        .iter()
+        .map(|(name, about)| format!("        '{}:{}'", name, about.replace('\'', "'\\''")))
+        .collect();
+    ZSH.replace("{commands}", &commands.join("\n"))
+}
+
+fn fish(commands: &[(&str, String)]) -> String {
+    let commands: Vec<_> = commands
+        .iter()
+        .map(|(name, about)| {
+            format!(
+                "complete -c ttrace -n __fish_use_subcommand -a {} -d '{}'",
+                name,
+                about.replace('\\', "\\\\").replace('\'', "\\'")
+            )
+        })
+        .collect();
+    FISH.replace("{commands}", &commands.join("\n"))
+}
+
+const BASH: &str = r#"_ttrace() {
+    local cur prev IFS=$'\n'
+    cur="${COMP_WORDS[COMP_CWORD]}"
+    prev="${COMP_WORDS[COMP_CWORD-1]}"
+    if [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "{commands}" -- "$cur"))
+        return
+    fi
+    case "$prev" in
+        -t|--tags)
+            COMPREPLY=($(compgen -W "$(ttrace complete tags 2>/dev/null)" -- "$cur"))
+            return
+            ;;
+    esac
+    case "${COMP_WORDS[1]}" in
+        start|rename)
+            COMPREPLY=($(compgen -W "$(ttrace complete descriptions 2>/dev/null)" -- "$cur"))
+            COMPREPLY=("${COMPREPLY[@]// /\\ }")
+            ;;
+        resume)
+            COMPREPLY=($(compgen -W "$(ttrace complete tasks 2>/dev/null | cut -f1)" -- "$cur"))
+            ;;
+    esac
+}
+complete -F _ttrace ttrace
+"#;
+
+const ZSH: &str = r#"#compdef ttrace
+
+_ttrace() {
+    local -a commands values
+    commands=(
+{commands}
+    )
+    if (( CURRENT == 2 )); then
+        _describe 'command' commands
+        return
+    fi
+    case "$words[CURRENT-1]" in
+        -t|--tags)
+            values=("${(@f)$(ttrace complete tags 2>/dev/null)}")
+            compadd -a values
+            return
+            ;;
+    esac
+    case "$words[2]" in
+        start|rename)
+            values=("${(@f)$(ttrace complete descriptions 2>/dev/null)}")
+            compadd -a values
+            ;;
+        resume)
+            values=("${(@f)$(ttrace complete tasks 2>/dev/null | sed 's/\t/:/')}")
+            _describe 'task' values
+            ;;
+    esac
+}
+
+compdef _ttrace ttrace
+"#;
+
+const FISH: &str = r#"complete -c ttrace -f
+{commands}
+complete -c ttrace -n '__fish_seen_subcommand_from start rename' -a '(ttrace complete descriptions 2>/dev/null)'
+complete -c ttrace -n '__fish_seen_subcommand_from start add edit' -s t -l tags -x -a '(ttrace complete tags 2>/dev/null)'
+complete -c ttrace -n '__fish_seen_subcommand_from resume' -a '(ttrace complete tasks 2>/dev/null)'
+"#;
@@ -1,36 +1,536 @@
-use std::{fs, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    env,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
+use chrono::{NaiveDate, TimeDelta, Weekday};
 use expanduser::expanduser;
-use eyre::Context;
-use serde::Deserialize;
+use eyre::{eyre, Context};
+use serde::{de::DeserializeOwned, de::Error, Deserialize, Deserializer};
 
-#[derive(Debug, Deserialize)]
+use crate::task::Rounding;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug)]
 pub struct Config {
-    path: PathBuf,
+    file: Option<PathBuf>,
+    path: Setting<PathBuf>,
+    profile: Setting<String>,
+    profiles: Vec<Profile>,
+    rounding: Option<Rounding>,
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            path: expanduser("~/.local/state/ttrack").unwrap(),
-        }
-    }
+#[derive(Debug, Clone)]
+pub struct Profile {
+    name: String,
+    database: Setting<PathBuf>,
+    tags: Setting<Vec<String>>,
+    hours: Option<Setting<WorkingHours>>,
+    since: Option<Setting<NaiveDate>>,
+    rounding: Option<Setting<Rounding>>,
+    rates: Setting<BTreeMap<String, Rate>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rate {
+    pub rate: f64,
+    pub currency: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkingHours {
+    pub monday: f64,
+    pub tuesday: f64,
+    pub wednesday: f64,
+    pub thursday: f64,
+    pub friday: f64,
+    pub saturday: f64,
+    pub sunday: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Setting<T> {
+    value: T,
+    source: Source,
+}
+
+#[derive(Debug, Clone)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    State(PathBuf),
+    Environment(&'static str),
+    Flag,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    path: Option<String>,
+    profile: Option<String>,
+    database: Option<String>,
+    tags: Vec<String>,
+    hours: Option<WorkingHours>,
+    #[serde(deserialize_with = "date")]
+    since: Option<NaiveDate>,
+    rounding: Option<Rounding>,
+    rates: BTreeMap<String, Rate>,
+    profiles: BTreeMap<String, ProfileFile>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ProfileFile {
+    database: Option<String>,
+    tags: Vec<String>,
+    hours: Option<WorkingHours>,
+    #[serde(deserialize_with = "date")]
+    since: Option<NaiveDate>,
+    rounding: Option<Rounding>,
+    rates: BTreeMap<String, Rate>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DateValue {
+    Toml(toml::value::Datetime),
+    Text(String),
 }
 
 impl Config {
-    pub fn load() -> eyre::Result<Self> {
-        let config = fs::read_to_string("~/.ttrack.json")
-            .map(|content| {
-                serde_json::from_str(content.as_str())
-                    .wrap_err("ERROR \"could not parse the '~/.ttrack.json' config!\"")
-                    .with_context(|| content)
-            })
-            .unwrap_or(Ok(Self::default()))?;
-        fs::create_dir_all(&config.path)?;
+    pub fn load(
+        file: Option<&Path>,
+        profile: Option<&str>,
+        rounding: Option<Rounding>,
+    ) -> eyre::Result<Self> {
+        let file = match file {
+            Some(file) if !file.exists() => {
+                return Err(eyre!("the config file does not exist: {}", file.display()));
+            }
+            Some(file) => Some(file.to_path_buf()),
+            None => find_config_file()?,
+        };
+        let content = match &file {
+            Some(file) => read_config_file(file)?,
+            None => ConfigFile::default(),
+        };
+        let source = || match &file {
+            Some(file) => Source::File(file.clone()),
+            None => Source::Default,
+        };
+
+        let mut path = Setting::new(default_path()?);
+        if let Some(value) = content.path {
+            path = Setting::with_source(expand(&value)?, source());
+        }
+        if let Some(value) = env_var("TTRACE_PATH")? {
+            path = Setting::with_source(expand(&value)?, Source::Environment("TTRACE_PATH"));
+        }
+
+        fs::create_dir_all(path.value())
+            .wrap_err("could not create the directory for the database")
+            .with_context(|| path.value().display().to_string())?;
+
+        let mut profiles = vec![Profile {
+            name: DEFAULT_PROFILE.to_owned(),
+            database: match content.database {
+                Some(database) => Setting::with_source(expand(&database)?, source()),
+                None => Setting::new(path.value().join("database.db")),
+            },
+            tags: configured(content.tags, source()),
+            hours: content
+                .hours
+                .map(|hours| Setting::with_source(hours, source())),
+            since: content
+                .since
+                .map(|since| Setting::with_source(since, source())),
+            rounding: content
+                .rounding
+                .map(|rounding| Setting::with_source(rounding, source())),
+            rates: configured(normalize_rates(content.rates), source()),
+        }];
+        for (name, profile) in content.profiles {
+            if name == DEFAULT_PROFILE {
+                return Err(eyre!(
+                    "the profile '{}' is configured by the top level settings",
+                    DEFAULT_PROFILE
+                ));
+            }
+            profiles.push(Profile {
+                database: match profile.database {
+                    Some(database) => Setting::with_source(expand(&database)?, source()),
+                    None => Setting::new(path.value().join(format!("{}.db", name))),
+                },
+                name,
+                tags: configured(profile.tags, source()),
+                hours: profile
+                    .hours
+                    .map(|hours| Setting::with_source(hours, source())),
+                since: profile
+                    .since
+                    .map(|since| Setting::with_source(since, source())),
+                rounding: profile
+                    .rounding
+                    .map(|rounding| Setting::with_source(rounding, source())),
+                rates: configured(normalize_rates(profile.rates), source()),
+            });
+        }
+
+        let state = path.value().join("profile");
+        let mut active = Setting::new(DEFAULT_PROFILE.to_owned());
+        if let Some(value) = content.profile {
+            active = Setting::with_source(value, source());
+        }
+        if let Some(value) = read_state(&state)? {
+            active = Setting::with_source(value, Source::State(state));
+        }
+        if let Some(value) = env_var("TTRACE_PROFILE")? {
+            active = Setting::with_source(value, Source::Environment("TTRACE_PROFILE"));
+        }
+        if let Some(value) = profile {
+            active = Setting::with_source(value.to_owned(), Source::Flag);
+        }
+        if let Some(profile) = profiles
+            .iter_mut()
+            .find(|profile| &profile.name == active.value())
+        {
+            apply_environment(profile)?;
+        }
+
+        let config = Self {
+            file,
+            path,
+            profile: active,
+            profiles,
+            rounding,
+        };
+        config.find_profile(config.profile.value())?;
         Ok(config)
     }
 
+    pub fn profile(&self) -> &Profile {
+        self.find_profile(self.profile.value())
+            .expect("the active profile is validated when loading")
+    }
+
+    pub fn active_profile(&self) -> &Setting<String> {
+        &self.profile
+    }
+
+    pub fn rounding(&self) -> Option<Setting<Rounding>> {
+        if let Some(rounding) = self.rounding {
+            return Some(Setting::with_source(rounding, Source::Flag));
+        }
+        self.profile().rounding.clone()
+    }
+
+    pub fn profiles(&self) -> impl Iterator<Item = &Profile> {
+        self.profiles.iter()
+    }
+
+    pub fn use_profile(&self, name: &str) -> eyre::Result<()> {
+        self.find_profile(name)?;
+        let state = self.path.value().join("profile");
+        fs::write(&state, name)
+            .wrap_err_with(|| format!("could not write the profile to {}", state.display()))
+    }
+
+    fn find_profile(&self, name: &str) -> eyre::Result<&Profile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .ok_or_else(|| eyre!("the profile '{}' is not configured", name))
+    }
+
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_deref()
+    }
+
+    pub fn path(&self) -> &Setting<PathBuf> {
+        &self.path
+    }
+
     pub fn database_path(&self) -> PathBuf {
-        self.path.join("database.db")
+        self.profile().database.value().clone()
+    }
+
+    pub fn socket_path(&self) -> PathBuf {
+        self.profile().database.value().with_extension("sock")
+    }
+
+    pub fn backup_path(&self, version: usize) -> PathBuf {
+        let database = self.profile().database.value();
+        let name = database
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or("database");
+        database.with_file_name(format!("{}.v{}.backup.db", name, version))
+    }
+}
+
+impl Profile {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn database(&self) -> &Setting<PathBuf> {
+        &self.database
+    }
+
+    pub fn tags(&self) -> &Setting<Vec<String>> {
+        &self.tags
+    }
+
+    pub fn hours(&self) -> Option<&Setting<WorkingHours>> {
+        self.hours.as_ref()
+    }
+
+    pub fn target(&self, weekday: Weekday) -> TimeDelta {
+        match &self.hours {
+            Some(hours) => hours.value().target(weekday),
+            None => WorkingHours::full_time().target(weekday),
+        }
+    }
+
+    pub fn since(&self) -> Option<&Setting<NaiveDate>> {
+        self.since.as_ref()
+    }
+
+    pub fn rates(&self) -> &Setting<BTreeMap<String, Rate>> {
+        &self.rates
+    }
+
+    pub fn rate(&self, tag: &str) -> Option<&Rate> {
+        self.rates.value().get(tag)
+    }
+}
+
+impl Display for Rate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.rate, self.currency)
+    }
+}
+
+impl WorkingHours {
+    pub fn full_time() -> Self {
+        Self {
+            monday: 8.0,
+            tuesday: 8.0,
+            wednesday: 8.0,
+            thursday: 8.0,
+            friday: 8.0,
+            ..Self::default()
+        }
+    }
+
+    pub fn target(&self, weekday: Weekday) -> TimeDelta {
+        let hours = match weekday {
+            Weekday::Mon => self.monday,
+            Weekday::Tue => self.tuesday,
+            Weekday::Wed => self.wednesday,
+            Weekday::Thu => self.thursday,
+            Weekday::Fri => self.friday,
+            Weekday::Sat => self.saturday,
+            Weekday::Sun => self.sunday,
+        };
+        TimeDelta::seconds((hours * 3600.0).round() as i64)
+    }
+}
+
+impl Display for WorkingHours {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "mon={} tue={} wed={} thu={} fri={} sat={} sun={}",
+            self.monday,
+            self.tuesday,
+            self.wednesday,
+            self.thursday,
+            self.friday,
+            self.saturday,
+            self.sunday
+        )
+    }
+}
+
+impl<T> Setting<T> {
+    pub fn new(value: T) -> Self {
+        Self::with_source(value, Source::Default)
+    }
+
+    pub fn with_source(value: T, source: Source) -> Self {
+        Self { value, source }
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::File(path) => write!(f, "file {}", path.display()),
+            Self::State(path) => write!(f, "state {}", path.display()),
+            Self::Environment(name) => write!(f, "environment variable {}", name),
+            Self::Flag => write!(f, "command line"),
+        }
+    }
+}
+
+fn find_config_file() -> eyre::Result<Option<PathBuf>> {
+    if let Some(file) = env_var("TTRACE_CONFIG")? {
+        let file = expand(&file)?;
+        if !file.exists() {
+            return Err(eyre!("the config file does not exist: {}", file.display()));
+        }
+        return Ok(Some(file));
+    }
+    let config_home = match env_var("XDG_CONFIG_HOME")? {
+        Some(config_home) => PathBuf::from(config_home),
+        None => expand("~/.config")?,
+    };
+    let candidates = [
+        config_home.join("ttrace/config.toml"),
+        config_home.join("ttrace/config.json"),
+        expand("~/.ttrack.json")?,
+    ];
+    Ok(candidates.into_iter().find(|file| file.exists()))
+}
+
+fn read_config_file(file: &Path) -> eyre::Result<ConfigFile> {
+    let content = fs::read_to_string(file)
+        .wrap_err_with(|| format!("could not read the config file {}", file.display()))?;
+    let config = match file.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => serde_json::from_str(&content).map_err(eyre::Error::from),
+        _ => toml::from_str(&content).map_err(eyre::Error::from),
+    };
+    config.wrap_err_with(|| format!("could not parse the config file {}", file.display()))
+}
+
+fn read_state(state: &Path) -> eyre::Result<Option<String>> {
+    if !state.exists() {
+        return Ok(None);
+    }
+    let value = fs::read_to_string(state)
+        .wrap_err_with(|| format!("could not read the profile from {}", state.display()))?;
+    let value = value.trim();
+    Ok((!value.is_empty()).then(|| value.to_owned()))
+}
+
+fn date<'de, D>(deserializer: D) -> Result<Option<NaiveDate>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let date = match DateValue::deserialize(deserializer)? {
+        DateValue::Toml(datetime) => match (datetime.date, datetime.time) {
+            (Some(date), None) => {
+                NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())
+            }
+            _ => None,
+        }
+        .ok_or_else(|| D::Error::custom(format!("expected a date: {}", datetime)))?,
+        DateValue::Text(text) => NaiveDate::from_str(&text).map_err(D::Error::custom)?,
+    };
+    Ok(Some(date))
+}
+
+fn apply_environment(profile: &mut Profile) -> eyre::Result<()> {
+    if let Some(value) = env_var("TTRACE_DATABASE")? {
+        let source = Source::Environment("TTRACE_DATABASE");
+        profile.database = Setting::with_source(expand(&value)?, source);
+    }
+    if let Some(value) = env_var("TTRACE_TAGS")? {
+        let tags = value
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_owned)
+            .collect();
+        profile.tags = Setting::with_source(tags, Source::Environment("TTRACE_TAGS"));
+    }
+    if let Some(value) = env_var("TTRACE_HOURS")? {
+        let hours = env_toml("TTRACE_HOURS", &value)?;
+        profile.hours = Some(Setting::with_source(
+            hours,
+            Source::Environment("TTRACE_HOURS"),
+        ));
+    }
+    if let Some(value) = env_var("TTRACE_SINCE")? {
+        let since = NaiveDate::from_str(value.trim())
+            .wrap_err("could not parse TTRACE_SINCE")
+            .with_context(|| value.clone())?;
+        profile.since = Some(Setting::with_source(
+            since,
+            Source::Environment("TTRACE_SINCE"),
+        ));
+    }
+    if let Some(value) = env_var("TTRACE_ROUNDING")? {
+        let rounding = Rounding::from_str(&value).wrap_err("could not parse TTRACE_ROUNDING")?;
+        profile.rounding = Some(Setting::with_source(
+            rounding,
+            Source::Environment("TTRACE_ROUNDING"),
+        ));
     }
+    if let Some(value) = env_var("TTRACE_RATES")? {
+        let rates = normalize_rates(env_toml("TTRACE_RATES", &value)?);
+        profile.rates = Setting::with_source(rates, Source::Environment("TTRACE_RATES"));
+    }
+    Ok(())
+}
+
+fn env_toml<T: DeserializeOwned>(name: &str, value: &str) -> eyre::Result<T> {
+    #[derive(Deserialize)]
+    struct Document<T> {
+        value: T,
+    }
+    let document: Document<T> = toml::from_str(&format!("value = {}", value))
+        .wrap_err_with(|| format!("could not parse {}", name))
+        .with_context(|| value.to_owned())?;
+    Ok(document.value)
+}
+
+fn configured<T: Default + PartialEq>(value: T, source: Source) -> Setting<T> {
+    match value == T::default() {
+        true => Setting::new(value),
+        false => Setting::with_source(value, source),
+    }
+}
+
+fn normalize_rates(rates: BTreeMap<String, Rate>) -> BTreeMap<String, Rate> {
+    rates
+        .into_iter()
+        .map(|(tag, rate)| (tag.trim().to_ascii_lowercase(), rate))
+        .collect()
+}
+
+fn default_path() -> eyre::Result<PathBuf> {
+    expand("~/.local/state/ttrack")
+}
+
+fn env_var(name: &str) -> eyre::Result<Option<String>> {
+    match env::var(name) {
+        Ok(value) if value.trim().is_empty() => Ok(None),
+        Ok(value) => Ok(Some(value)),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(error) => Err(error).with_context(|| format!("could not read {}", name)),
+    }
+}
+
+fn expand(path: &str) -> eyre::Result<PathBuf> {
+    expanduser(path)
+        .wrap_err("could not expand the path")
+        .with_context(|| path.to_owned())
 }
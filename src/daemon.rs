@@ -0,0 +1,168 @@
+use chrono::NaiveDate;
+use eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    day::Day,
+    task::{DayWithTasks, Task},
+};
+
+pub use self::client::Client;
+pub use self::server::{bind, serve};
+
+mod client;
+mod server;
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum Request {
+    Start {
+        description: String,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(default)]
+        fuzzy: bool,
+        #[serde(default = "billable")]
+        billable: bool,
+    },
+    Stop,
+    Current,
+    Day {
+        #[serde(default)]
+        date: Option<NaiveDate>,
+    },
+    Subscribe,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Response {
+    ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task: Option<TaskData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    day: Option<DayData>,
+}
+
+#[derive(Serialize)]
+struct Event {
+    event: &'static str,
+    task: Option<TaskData>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TaskData {
+    day: Day,
+    #[serde(flatten)]
+    task: Task<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DayData {
+    #[serde(flatten)]
+    day: Day,
+    #[serde(default)]
+    note: Option<String>,
+    tasks: Vec<Task<u64>>,
+}
+
+impl Response {
+    fn ok() -> Self {
+        Self {
+            ok: true,
+            ..Self::default()
+        }
+    }
+
+    fn error(error: &eyre::Report) -> Self {
+        let error = error
+            .chain()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(": ");
+        Self {
+            error: Some(error),
+            ..Self::default()
+        }
+    }
+
+    fn with_task(mut self, task: &Task<Day>) -> Self {
+        self.task = Some(TaskData::new(task));
+        self
+    }
+
+    fn with_day(mut self, day: &DayWithTasks) -> Self {
+        self.day = Some(DayData {
+            day: *day.day(),
+            note: day.note().map(str::to_owned),
+            tasks: day.tasks().map(detach).collect(),
+        });
+        self
+    }
+
+    pub fn into_result(self) -> eyre::Result<Self> {
+        if self.ok {
+            return Ok(self);
+        }
+        let error = self
+            .error
+            .unwrap_or_else(|| "the daemon could not handle the request".to_owned());
+        Err(eyre!(error))
+    }
+
+    pub fn task(self) -> Option<Task<Day>> {
+        self.task.map(TaskData::into_task)
+    }
+
+    pub fn day(self) -> Option<DayWithTasks> {
+        let data = self.day?;
+        let tasks = data
+            .tasks
+            .into_iter()
+            .map(|task| attach(task, data.day))
+            .collect();
+        Some(DayWithTasks::new(data.day, tasks).with_note(data.note))
+    }
+}
+
+impl TaskData {
+    fn new(task: &Task<Day>) -> Self {
+        Self {
+            day: task.day(),
+            task: detach(task),
+        }
+    }
+
+    fn into_task(self) -> Task<Day> {
+        attach(self.task, self.day)
+    }
+}
+
+fn detach(task: &Task<Day>) -> Task<u64> {
+    Task::new(
+        task.id(),
+        task.day_id(),
+        task.start(),
+        task.end(),
+        task.description().to_owned(),
+        task.tags().to_vec(),
+    )
+    .with_billable(task.is_billable())
+}
+
+fn attach(task: Task<u64>, day: Day) -> Task<Day> {
+    Task::new(
+        task.id(),
+        day,
+        task.start(),
+        task.end(),
+        task.description().to_owned(),
+        task.tags().to_vec(),
+    )
+    .with_billable(task.is_billable())
+}
+
+fn billable() -> bool {
+    true
+}
@@ -0,0 +1,55 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    time::Duration,
+};
+
+use eyre::{eyre, Context};
+
+use crate::config::Config;
+
+use super::{Request, Response};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct Client {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl Client {
+    pub fn connect(config: &Config) -> eyre::Result<Option<Self>> {
+        let path = config.socket_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let Ok(stream) = UnixStream::connect(&path) else {
+            return Ok(None);
+        };
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let reader = BufReader::new(stream.try_clone()?);
+        Ok(Some(Self {
+            reader,
+            writer: stream,
+        }))
+    }
+
+    pub fn request(&mut self, request: &Request) -> eyre::Result<Response> {
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        self.writer
+            .write_all(line.as_bytes())
+            .wrap_err("could not send the request to the daemon")?;
+        line.clear();
+        self.reader
+            .read_line(&mut line)
+            .wrap_err("could not read the response of the daemon")?;
+        if line.is_empty() {
+            return Err(eyre!("the daemon closed the connection"));
+        }
+        let response: Response =
+            serde_json::from_str(&line).wrap_err("could not parse the response of the daemon")?;
+        response.into_result()
+    }
+}
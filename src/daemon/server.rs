@@ -0,0 +1,193 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::Path,
+    sync::mpsc::{channel, RecvTimeoutError, Sender},
+    thread,
+    time::Duration,
+};
+
+use chrono::Local;
+use eyre::{eyre, Context};
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::{database::data_version, day::DayRepository, task::TaskRepository};
+
+use super::{Event, Request, Response, TaskData};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+enum Message {
+    Request(Request, Sender<Response>),
+    Subscribe(UnixStream),
+}
+
+pub fn serve(
+    listener: UnixListener,
+    connection: &Connection,
+    days: &DayRepository,
+    tasks: &TaskRepository,
+) -> eyre::Result<()> {
+    let (sender, receiver) = channel();
+    thread::spawn(move || accept(listener, sender));
+    let mut subscribers: Vec<UnixStream> = Vec::new();
+    let mut version = data_version(connection)?;
+    loop {
+        let changed = match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(Message::Request(request, reply)) => {
+                let changed = matches!(request, Request::Start { .. } | Request::Stop);
+                let response =
+                    handle(days, tasks, request).unwrap_or_else(|error| Response::error(&error));
+                let changed = changed && response.ok;
+                let _ = reply.send(response);
+                changed
+            }
+            Ok(Message::Subscribe(stream)) => {
+                subscribers.push(stream);
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(eyre!("the daemon stopped accepting connections"));
+            }
+        };
+        let current = data_version(connection)?;
+        if changed || current != version {
+            version = current;
+            notify(days, tasks, &mut subscribers)?;
+        }
+    }
+}
+
+pub fn bind(path: &Path) -> eyre::Result<UnixListener> {
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(eyre!("the daemon is already running: {}", path.display()));
+        }
+        fs::remove_file(path)
+            .wrap_err("could not remove the stale socket of the daemon")
+            .with_context(|| path.display().to_string())?;
+    }
+    UnixListener::bind(path)
+        .wrap_err("could not listen on the socket of the daemon")
+        .with_context(|| path.display().to_string())
+}
+
+fn accept(listener: UnixListener, sender: Sender<Message>) {
+    for stream in listener.incoming().flatten() {
+        let sender = sender.clone();
+        thread::spawn(move || serve_client(stream, sender));
+    }
+}
+
+fn serve_client(stream: UnixStream, sender: Sender<Message>) -> eyre::Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::Subscribe) => {
+                let subscriber = writer.try_clone()?;
+                subscriber.set_write_timeout(Some(WRITE_TIMEOUT))?;
+                write_line(&mut writer, &Response::ok())?;
+                sender.send(Message::Subscribe(subscriber))?;
+                continue;
+            }
+            Ok(request) => {
+                let (reply, response) = channel();
+                sender.send(Message::Request(request, reply))?;
+                response.recv()?
+            }
+            Err(error) => Response::error(&eyre!("could not parse the request: {}", error)),
+        };
+        write_line(&mut writer, &response)?;
+    }
+    Ok(())
+}
+
+fn handle(
+    days: &DayRepository,
+    tasks: &TaskRepository,
+    request: Request,
+) -> eyre::Result<Response> {
+    let today = Local::now().date_naive();
+    match request {
+        Request::Start {
+            description,
+            tags,
+            fuzzy,
+            billable,
+        } => {
+            let description = match fuzzy {
+                true => tasks.match_description(&description)?,
+                false => description,
+            };
+            tasks.begin("start");
+            let transaction = tasks.transaction()?;
+            tasks.carry_over(today)?;
+            let task = tasks.start(days.lookup(today)?, &description, &tags)?;
+            let task = match billable {
+                true => task,
+                false => tasks.set_billable(task, false)?,
+            };
+            transaction.commit()?;
+            Ok(Response::ok().with_task(&task))
+        }
+        Request::Stop => {
+            if tasks.running()?.is_none() {
+                return Ok(Response::ok());
+            }
+            tasks.begin("stop");
+            let transaction = tasks.transaction()?;
+            tasks.carry_over(today)?;
+            let task = tasks.stop(days.lookup(today)?)?;
+            transaction.commit()?;
+            Ok(Response::ok().with_task(&task))
+        }
+        Request::Current => match tasks.current(days.lookup(today)?) {
+            Ok(task) => Ok(Response::ok().with_task(&task)),
+            Err(_) => Ok(Response::ok()),
+        },
+        Request::Day { date } => {
+            let day = match date {
+                Some(date) => days.lookup(date)?,
+                None => days.lookup(today)?,
+            };
+            let note = days.note(&day)?;
+            let day = tasks.day_with_tasks(day)?.with_note(note);
+            Ok(Response::ok().with_day(&day))
+        }
+        Request::Subscribe => Err(eyre!("subscriptions are handled by the connection")),
+    }
+}
+
+fn notify(
+    days: &DayRepository,
+    tasks: &TaskRepository,
+    subscribers: &mut Vec<UnixStream>,
+) -> eyre::Result<()> {
+    if subscribers.is_empty() {
+        return Ok(());
+    }
+    let today = days.lookup(Local::now().date_naive())?;
+    let event = Event {
+        event: "changed",
+        task: tasks.current(today).ok().as_ref().map(TaskData::new),
+    };
+    subscribers.retain_mut(|subscriber| write_line(subscriber, &event).is_ok());
+    Ok(())
+}
+
+fn write_line(writer: &mut impl Write, value: &impl Serialize) -> eyre::Result<()> {
+    let mut line = serde_json::to_string(value)?;
+    line.push('\n');
+    writer
+        .write_all(line.as_bytes())
+        .wrap_err("could not write to the client of the daemon")
+}
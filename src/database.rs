@@ -1,12 +1,187 @@
 use std::rc::Rc;
 
+use eyre::{eyre, Context};
 use rusqlite::{Connection, OpenFlags};
 
 use crate::config::Config;
 
+// the index + 1 of a migration is the schema version it migrates to, only append new ones
+const MIGRATIONS: &[&str] = &[
+    // 1: the initial schema, created without a version by earlier releases
+    "CREATE TABLE IF NOT EXISTS days (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        date DATE NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS tasks (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        day_id INTEGER NOT NULL,
+        start DATE NOT NULL,
+        end DATE,
+        description TEXT NOT NULL
+    );",
+    // 2: tags of tasks
+    "CREATE TABLE IF NOT EXISTS tags (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE IF NOT EXISTS task_tags (
+        task_id INTEGER NOT NULL,
+        tag_id INTEGER NOT NULL,
+        PRIMARY KEY (task_id, tag_id)
+    );
+    CREATE VIEW IF NOT EXISTS tasks_with_tags AS
+    SELECT tasks.*, (
+        SELECT group_concat(tags.name, char(31))
+        FROM task_tags JOIN tags ON tags.id = task_tags.tag_id
+        WHERE task_tags.task_id = tasks.id
+    ) AS tags
+    FROM tasks;",
+    // 3: journal of the changes to tasks, used to undo them
+    "CREATE TABLE operations (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        created DATETIME NOT NULL
+    );
+    CREATE TABLE journal (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        operation_id INTEGER NOT NULL,
+        task_id INTEGER NOT NULL,
+        before TEXT,
+        after TEXT
+    );",
+    // 4: days off count as fulfilled working days
+    "ALTER TABLE days ADD COLUMN kind TEXT NOT NULL DEFAULT 'workday';",
+    // 5: a note on days off, e.g. the name of the holiday
+    "ALTER TABLE days ADD COLUMN note TEXT;",
+    // 6: tasks that are not billed to the client
+    "ALTER TABLE tasks ADD COLUMN billable INTEGER NOT NULL DEFAULT 1;",
+];
+
+pub struct Savepoint<'a> {
+    connection: &'a Connection,
+    released: bool,
+}
+
+impl<'a> Savepoint<'a> {
+    pub fn new(connection: &'a Connection) -> eyre::Result<Self> {
+        connection
+            .execute_batch("SAVEPOINT ttrace")
+            .wrap_err("could not begin a transaction")?;
+        Ok(Self {
+            connection,
+            released: false,
+        })
+    }
+
+    pub fn commit(mut self) -> eyre::Result<()> {
+        self.released = true;
+        self.connection
+            .execute_batch("RELEASE ttrace")
+            .wrap_err("could not commit the transaction")
+    }
+}
+
+impl Drop for Savepoint<'_> {
+    fn drop(&mut self) {
+        if !self.released {
+            let _ = self
+                .connection
+                .execute_batch("ROLLBACK TO ttrace; RELEASE ttrace");
+        }
+    }
+}
+
 pub fn open_database_connection(config: &Config) -> eyre::Result<Rc<Connection>> {
     let path = config.database_path();
     let flags = OpenFlags::default();
+    let mut connection = Connection::open_with_flags(path, flags)?;
+    migrate(&mut connection, config)?;
+    Ok(connection.into())
+}
+
+pub fn open_read_only_connection(config: &Config) -> eyre::Result<Option<Rc<Connection>>> {
+    let path = config.database_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX;
     let connection = Connection::open_with_flags(path, flags)?;
+    if schema_version(&connection)? != MIGRATIONS.len() {
+        return Ok(None);
+    }
+    Ok(Some(connection.into()))
+}
+
+#[cfg(test)]
+pub fn open_in_memory_connection() -> eyre::Result<Rc<Connection>> {
+    let mut connection = Connection::open_in_memory()?;
+    apply_migrations(&mut connection, 0)?;
     Ok(connection.into())
 }
+
+pub fn schema_version(connection: &Connection) -> eyre::Result<usize> {
+    connection
+        .query_row("PRAGMA user_version", (), |row| row.get(0))
+        .wrap_err("could not read the schema version of the database")
+}
+
+pub fn data_version(connection: &Connection) -> eyre::Result<u64> {
+    connection
+        .query_row("PRAGMA data_version", (), |row| row.get(0))
+        .wrap_err("could not read the data version of the database")
+}
+
+fn migrate(connection: &mut Connection, config: &Config) -> eyre::Result<()> {
+    let version = schema_version(connection)?;
+    if version > MIGRATIONS.len() {
+        return Err(eyre!(
+            "the database has the schema version {}, but only version {} is supported. please update ttrace.",
+            version,
+            MIGRATIONS.len()
+        ));
+    }
+    if version == MIGRATIONS.len() {
+        return Ok(());
+    }
+    if has_tables(connection)? {
+        backup(connection, config, version)?;
+    }
+    apply_migrations(connection, version)
+}
+
+fn apply_migrations(connection: &mut Connection, version: usize) -> eyre::Result<()> {
+    let transaction = connection.transaction()?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        transaction
+            .execute_batch(migration)
+            .wrap_err_with(|| format!("could not migrate the database to version {}", index + 1))?;
+    }
+    transaction.pragma_update(None, "user_version", MIGRATIONS.len())?;
+    transaction
+        .commit()
+        .wrap_err("could not commit the database migrations")
+}
+
+fn has_tables(connection: &Connection) -> eyre::Result<bool> {
+    let count: usize = connection.query_row(
+        "SELECT count(*) FROM sqlite_master WHERE type='table'",
+        (),
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn backup(connection: &Connection, config: &Config, version: usize) -> eyre::Result<()> {
+    let path = config.backup_path(version);
+    if path.exists() {
+        return Ok(());
+    }
+    let path = path
+        .to_str()
+        .ok_or_else(|| eyre!("the backup path is not valid unicode: {:?}", path))?;
+    connection
+        .execute("VACUUM INTO ?1", (path,))
+        .wrap_err("could not backup the database before migrating it")
+        .with_context(|| path.to_owned())?;
+    Ok(())
+}
@@ -2,9 +2,9 @@ use std::rc::Rc;
 
 use chrono::{Datelike, Days, Local, NaiveDate, Weekday};
 use eyre::{Context, ContextCompat};
-use rusqlite::{Connection, Params, Row};
+use rusqlite::{Connection, OptionalExtension, Params, Row};
 
-pub use dto::{Day, DayRef, DayReference};
+pub use dto::{Day, DayKind, DayRef, DayReference, END_OF_DAY};
 use someutil::NaiveWeekExt;
 
 mod dto;
@@ -14,20 +14,13 @@ pub struct DayRepository {
 }
 
 impl DayRepository {
-    pub fn new(connection: Rc<Connection>) -> eyre::Result<Self> {
-        let _ = connection.execute(
-            "CREATE TABLE IF NOT EXISTS days (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                date DATE NOT NULL
-            )",
-            (),
-        )?;
-        Ok(Self { connection })
+    pub fn new(connection: Rc<Connection>) -> Self {
+        Self { connection }
     }
 
     pub fn today(&self) -> eyre::Result<Day> {
         let date = Local::now().date_naive();
-        self.from_date(date)
+        self.lookup(date)
     }
 
     pub fn yesterday(&self) -> eyre::Result<Day> {
@@ -35,15 +28,12 @@ impl DayRepository {
             .date_naive()
             .checked_sub_days(Days::new(1))
             .wrap_err("could not get yesterdays date!")?;
-        self.from_date(date)
+        self.lookup(date)
     }
 
-    pub fn complete_week(&self, mut date: NaiveDate) -> eyre::Result<Vec<Day>> {
-        const ONE_DAY: Days = Days::new(1);
-        date.week(Weekday::Mon)
-            .iter_days()
-            .map(|date| self.from_date(date))
-            .collect()
+    pub fn complete_week(&self, date: NaiveDate) -> eyre::Result<Vec<Day>> {
+        let week = date.week(Weekday::Mon);
+        self.lookup_range(week.first_day(), week.last_day())
     }
 
     pub fn week_till_today(&self) -> eyre::Result<Vec<Day>> {
@@ -51,32 +41,111 @@ impl DayRepository {
         self.week_till_date(date)
     }
 
-    pub fn week_till_date(&self, mut date: NaiveDate) -> eyre::Result<Vec<Day>> {
-        const ONE_DAY: Days = Days::new(1);
-        date.week(Weekday::Mon)
+    pub fn week_till_date(&self, date: NaiveDate) -> eyre::Result<Vec<Day>> {
+        let week = date.week(Weekday::Mon);
+        self.lookup_range(week.first_day(), date)
+    }
+
+    pub fn lookup_range(&self, from: NaiveDate, to: NaiveDate) -> eyre::Result<Vec<Day>> {
+        let days = self.query(
+            "SELECT id, date, kind FROM days WHERE date >= ?1 AND date <= ?2 ORDER BY date",
+            (from, to),
+        )?;
+        let days = from
             .iter_days()
-            .filter(|day| *day <= date)
-            .map(|date| self.from_date(date))
-            .collect()
+            .take_while(|date| *date <= to)
+            .map(|date| {
+                days.iter()
+                    .find(|day| day.date() == date)
+                    .copied()
+                    .unwrap_or_else(|| Day::transient(date))
+            })
+            .collect();
+        Ok(days)
     }
 
     pub fn list_passed_days(&self, count: usize) -> eyre::Result<Vec<Day>> {
         self.query(
-            "SELECT id, date FROM days ORDER BY date DESC LIMIT ?1",
+            "SELECT id, date, kind FROM days
+             WHERE id IN (SELECT day_id FROM tasks)
+             ORDER BY date DESC
+             LIMIT ?1",
             (count,),
         )
     }
 
-    pub fn from_date(&self, date: NaiveDate) -> eyre::Result<Day> {
-        if let Ok(day) = self.from_date_or_none(&date) {
+    pub fn all(&self) -> eyre::Result<Vec<Day>> {
+        self.query("SELECT id, date, kind FROM days ORDER BY date", ())
+    }
+
+    pub fn first_day(&self) -> eyre::Result<Option<Day>> {
+        let days = self.query(
+            "SELECT id, date, kind FROM days
+             WHERE id IN (SELECT day_id FROM tasks) OR kind != 'workday'
+             ORDER BY date
+             LIMIT 1",
+            (),
+        )?;
+        Ok(days.into_iter().next())
+    }
+
+    pub fn set_kind(&self, day: Day, kind: DayKind) -> eyre::Result<Day> {
+        let day = self.persist(day)?;
+        self.connection
+            .execute("UPDATE days SET kind = ?1 WHERE id = ?2", (kind, day.id()))
+            .wrap_err("could not update the kind of the day")
+            .with_context(|| day.to_string())?;
+        self.day(day.id())
+    }
+
+    pub fn note(&self, day: &Day) -> eyre::Result<Option<String>> {
+        if day.is_transient() {
+            return Ok(None);
+        }
+        self.connection
+            .query_row("SELECT note FROM days WHERE id = ?1", (day.id(),), |row| {
+                row.get(0)
+            })
+            .wrap_err("could not query the note of the day")
+            .with_context(|| day.to_string())
+    }
+
+    pub fn set_note(&self, day: Day, note: Option<&str>) -> eyre::Result<Day> {
+        let day = self.persist(day)?;
+        self.connection
+            .execute("UPDATE days SET note = ?1 WHERE id = ?2", (note, day.id()))
+            .wrap_err("could not update the note of the day")
+            .with_context(|| day.to_string())?;
+        Ok(day)
+    }
+
+    pub fn lookup(&self, date: NaiveDate) -> eyre::Result<Day> {
+        let day = self.find(&date)?;
+        Ok(day.unwrap_or_else(|| Day::transient(date)))
+    }
+
+    pub fn persist(&self, day: Day) -> eyre::Result<Day> {
+        if !day.is_transient() {
+            return Ok(day);
+        }
+        if let Some(day) = self.find(&day.date())? {
             return Ok(day);
         }
-        self.insert_from_date(&date);
-        self.from_date_or_none(&date)
+        self.insert_from_date(&day.date())?;
+        self.find(&day.date())?
+            .wrap_err("could not get newly created day")
     }
 
-    fn from_date_or_none(&self, date: &NaiveDate) -> eyre::Result<Day> {
-        self.get("SELECT id, date FROM days WHERE date = ?1", (date,))
+    fn find(&self, date: &NaiveDate) -> eyre::Result<Option<Day>> {
+        self.connection
+            .query_row(
+                "SELECT id, date, kind FROM days WHERE date = ?1",
+                (date,),
+                day_from_row,
+            )
+            .optional()
+            .wrap_err("could not query day")
+            .with_context(|| date.to_string())
     }
 
     pub fn resolve(&self, reference: DayReference) -> eyre::Result<Day> {
@@ -87,7 +156,25 @@ impl DayRepository {
     }
 
     pub fn day(&self, id: u64) -> eyre::Result<Day> {
-        self.get("SELECT id, date FROM days WHERE id = ?1", (id,))
+        self.get("SELECT id, date, kind FROM days WHERE id = ?1", (id,))
+    }
+
+    pub fn restore(&self, day: Day, note: Option<&str>) -> eyre::Result<Day> {
+        self.connection
+            .execute(
+                "INSERT INTO days (id, date, kind, note) VALUES (?1, ?2, ?3, ?4)",
+                (day.id(), day.date(), day.kind(), note),
+            )
+            .wrap_err("could not restore the day")
+            .with_context(|| day.to_string())?;
+        self.day(day.id())
+    }
+
+    pub fn clear(&self) -> eyre::Result<()> {
+        self.connection
+            .execute("DELETE FROM days", ())
+            .wrap_err("could not delete the days")?;
+        Ok(())
     }
 
     fn insert_from_date(&self, date: &NaiveDate) -> eyre::Result<()> {
@@ -112,7 +199,6 @@ impl DayRepository {
             .query_map(parameters, day_from_row)
             .wrap_err("could not execute sql statement")
             .with_context(|| query.to_owned())?
-            .into_iter()
             .collect::<Result<_, _>>()
             .wrap_err("cannot convert tasks from sql statement")
             .with_context(|| query.to_owned())
@@ -122,5 +208,6 @@ impl DayRepository {
 pub fn day_from_row(row: &Row) -> rusqlite::Result<Day> {
     let id = row.get("id")?;
     let date = row.get("date")?;
-    Ok(Day::new(id, date))
+    let kind = row.get("kind")?;
+    Ok(Day::new(id, date, kind))
 }
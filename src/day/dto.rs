@@ -1,7 +1,9 @@
 pub use {
     day_reference::DayReference,
-    value::{Day, DayRef},
+    kind::DayKind,
+    value::{Day, DayRef, END_OF_DAY},
 };
 
 mod day_reference;
+mod kind;
 mod value;
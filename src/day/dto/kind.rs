@@ -0,0 +1,69 @@
+use std::{fmt::Display, str::FromStr};
+
+use eyre::eyre;
+use rusqlite::{
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+    ToSql,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DayKind {
+    #[default]
+    Workday,
+    Vacation,
+    Sick,
+    Holiday,
+    Weekend,
+}
+
+impl DayKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Workday => "workday",
+            Self::Vacation => "vacation",
+            Self::Sick => "sick",
+            Self::Holiday => "holiday",
+            Self::Weekend => "weekend",
+        }
+    }
+
+    pub fn is_fulfilled(&self) -> bool {
+        !matches!(self, Self::Workday)
+    }
+}
+
+impl Display for DayKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for DayKind {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "workday" | "work" => Ok(Self::Workday),
+            "vacation" => Ok(Self::Vacation),
+            "sick" => Ok(Self::Sick),
+            "holiday" | "public-holiday" => Ok(Self::Holiday),
+            "weekend" => Ok(Self::Weekend),
+            _ => Err(eyre!("could not convert string to day kind: {}", s)),
+        }
+    }
+}
+
+impl FromSql for DayKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let value = value.as_str()?;
+        Self::from_str(value).map_err(|error| FromSqlError::Other(error.into()))
+    }
+}
+
+impl ToSql for DayKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.name()))
+    }
+}
@@ -2,12 +2,17 @@ use std::fmt::Display;
 
 use chrono::{Local, NaiveDate, NaiveTime};
 use rusqlite::Row;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone, Serialize)]
+use super::DayKind;
+
+pub const END_OF_DAY: NaiveTime = NaiveTime::from_hms_nano_opt(23, 59, 59, 999_999_999).unwrap();
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct Day {
     id: u64,
     date: NaiveDate,
+    kind: DayKind,
 }
 
 pub trait DayRef {
@@ -16,25 +21,41 @@ pub trait DayRef {
 }
 
 impl Day {
-    pub fn new(id: u64, date: NaiveDate) -> Self {
-        Self { id, date }
+    pub fn new(id: u64, date: NaiveDate, kind: DayKind) -> Self {
+        Self { id, date, kind }
+    }
+
+    pub fn transient(date: NaiveDate) -> Self {
+        Self {
+            id: 0,
+            date,
+            kind: DayKind::default(),
+        }
     }
 
     pub fn id(&self) -> u64 {
         self.id
     }
 
+    pub fn is_transient(&self) -> bool {
+        self.id == 0
+    }
+
     pub fn date(&self) -> NaiveDate {
         self.date
     }
 
+    pub fn kind(&self) -> DayKind {
+        self.kind
+    }
+
     pub fn is_today(&self) -> bool {
         self.date == Local::now().date_naive()
     }
 
     pub fn time(&self) -> NaiveTime {
         if !self.is_today() {
-            return NaiveTime::from_hms_opt(23, 59, 59).unwrap();
+            return END_OF_DAY;
         }
         Local::now().time()
     }
@@ -42,12 +63,19 @@ impl Day {
 
 impl Display for Day {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_transient() {
+            return write!(f, "day date={}", self.date.format("%Y-%m-%d"));
+        }
         write!(
             f,
             "day id={} date={}",
             self.id,
             self.date.format("%Y-%m-%d")
-        )
+        )?;
+        if self.kind != DayKind::Workday {
+            write!(f, " kind={}", self.kind)?;
+        }
+        Ok(())
     }
 }
 
@@ -0,0 +1,69 @@
+use std::{io::Write, str::FromStr};
+
+use chrono::{DateTime, Days, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use eyre::eyre;
+
+use crate::{day::END_OF_DAY, task::DayWithTasks};
+
+pub use self::timeclock::LAST_SECOND;
+
+mod csv;
+mod ics;
+mod timeclock;
+mod timewarrior;
+
+pub enum ExportFormat {
+    Csv,
+    Timewarrior,
+    Timeclock,
+    Ics,
+}
+
+pub enum ExportRows {
+    Tasks,
+    Groups,
+}
+
+impl FromStr for ExportFormat {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "timewarrior" | "timew" => Ok(Self::Timewarrior),
+            "timeclock" | "ledger" => Ok(Self::Timeclock),
+            "ics" | "ical" | "icalendar" => Ok(Self::Ics),
+            _ => Err(eyre!("the export format is not supported: {}", s)),
+        }
+    }
+}
+
+pub fn export(
+    writer: impl Write,
+    format: ExportFormat,
+    rows: ExportRows,
+    profile: &str,
+    days: &[DayWithTasks],
+) -> eyre::Result<()> {
+    match format {
+        ExportFormat::Csv => csv::export(writer, rows, days),
+        ExportFormat::Timewarrior => timewarrior::export(writer, days),
+        ExportFormat::Timeclock => timeclock::export(writer, days),
+        ExportFormat::Ics => ics::export(writer, profile, days),
+    }
+}
+
+fn utc(date: NaiveDate, time: NaiveTime) -> eyre::Result<DateTime<Utc>> {
+    let time = if time == END_OF_DAY {
+        date.checked_add_days(Days::new(1))
+            .ok_or_else(|| eyre!("the date is out of range: {}", date))?
+            .and_time(NaiveTime::MIN)
+    } else {
+        date.and_time(time)
+    };
+    let time = Local
+        .from_local_datetime(&time)
+        .earliest()
+        .ok_or_else(|| eyre!("the time does not exist in the local time zone: {}", time))?;
+    Ok(time.with_timezone(&Utc))
+}
@@ -0,0 +1,84 @@
+use std::io::Write;
+
+use chrono::{NaiveDate, NaiveTime};
+use eyre::Context;
+use serde::Serialize;
+
+use crate::{
+    day::END_OF_DAY,
+    task::{DayWithTasks, RoundingScope},
+};
+
+use super::ExportRows;
+
+#[derive(Serialize)]
+struct TaskRow<'a> {
+    date: NaiveDate,
+    start: String,
+    end: String,
+    minutes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rounded_minutes: Option<i64>,
+    description: &'a str,
+    tags: String,
+}
+
+#[derive(Serialize)]
+struct GroupRow<'a> {
+    date: NaiveDate,
+    minutes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rounded_minutes: Option<i64>,
+    description: &'a str,
+    tags: String,
+}
+
+pub fn export(writer: impl Write, rows: ExportRows, days: &[DayWithTasks]) -> eyre::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for day in days {
+        let date = day.day().date();
+        match rows {
+            ExportRows::Tasks => {
+                for task in day.tasks() {
+                    writer.serialize(TaskRow {
+                        date,
+                        start: time(task.start()),
+                        end: task.end().map(time).unwrap_or_default(),
+                        minutes: task.delta().num_minutes(),
+                        rounded_minutes: day
+                            .rounding()
+                            .filter(|rounding| rounding.per() == RoundingScope::Task)
+                            .map(|rounding| rounding.round(task.delta()).num_minutes()),
+                        description: task.description(),
+                        tags: task.tags().join(" "),
+                    })?;
+                }
+            }
+            ExportRows::Groups => {
+                for group in day.task_groups() {
+                    let mut tags: Vec<_> = group.tasks().flat_map(|task| task.tags()).collect();
+                    tags.sort();
+                    tags.dedup();
+                    writer.serialize(GroupRow {
+                        date,
+                        minutes: group.delta().num_minutes(),
+                        rounded_minutes: day
+                            .rounding()
+                            .and_then(|rounding| group.rounded(rounding))
+                            .map(|rounded| rounded.num_minutes()),
+                        description: group.description(),
+                        tags: tags.into_iter().cloned().collect::<Vec<_>>().join(" "),
+                    })?;
+                }
+            }
+        }
+    }
+    writer.flush().wrap_err("could not write the csv export")
+}
+
+fn time(time: NaiveTime) -> String {
+    if time == END_OF_DAY {
+        return "24:00".to_owned();
+    }
+    time.format("%H:%M").to_string()
+}
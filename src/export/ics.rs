@@ -0,0 +1,73 @@
+use std::io::Write;
+
+use chrono::{DateTime, Utc};
+use eyre::Context;
+
+use crate::task::DayWithTasks;
+
+use super::utc;
+
+const LINE_LIMIT: usize = 75;
+
+pub fn export(mut writer: impl Write, profile: &str, days: &[DayWithTasks]) -> eyre::Result<()> {
+    let now = Utc::now();
+    line(&mut writer, "BEGIN:VCALENDAR")?;
+    line(&mut writer, "VERSION:2.0")?;
+    line(&mut writer, "PRODID:-//ttrace//ttrace//EN")?;
+    for day in days {
+        let date = day.day().date();
+        for task in day.tasks() {
+            let Some(end) = task.end() else {
+                continue;
+            };
+            line(&mut writer, "BEGIN:VEVENT")?;
+            line(
+                &mut writer,
+                &format!("UID:task-{}.{}@ttrace", task.id(), escape(profile)),
+            )?;
+            line(&mut writer, &format!("DTSTAMP:{}", time(now)))?;
+            line(
+                &mut writer,
+                &format!("DTSTART:{}", time(utc(date, task.start())?)),
+            )?;
+            line(&mut writer, &format!("DTEND:{}", time(utc(date, end)?)))?;
+            line(
+                &mut writer,
+                &format!("SUMMARY:{}", escape(task.description())),
+            )?;
+            if !task.tags().is_empty() {
+                let tags: Vec<_> = task.tags().iter().map(|tag| escape(tag)).collect();
+                line(&mut writer, &format!("CATEGORIES:{}", tags.join(",")))?;
+            }
+            line(&mut writer, "END:VEVENT")?;
+        }
+    }
+    line(&mut writer, "END:VCALENDAR")?;
+    writer.flush().wrap_err("could not write the ics export")
+}
+
+fn time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn line(writer: &mut impl Write, line: &str) -> eyre::Result<()> {
+    let mut length = 0;
+    for char in line.chars() {
+        if length + char.len_utf8() > LINE_LIMIT {
+            write!(writer, "\r\n ")?;
+            length = 1;
+        }
+        write!(writer, "{}", char)?;
+        length += char.len_utf8();
+    }
+    write!(writer, "\r\n")?;
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use std::io::Write;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use eyre::Context;
+
+use crate::{day::END_OF_DAY, task::DayWithTasks};
+
+pub const LAST_SECOND: NaiveTime = NaiveTime::from_hms_opt(23, 59, 59).unwrap();
+
+pub fn export(mut writer: impl Write, days: &[DayWithTasks]) -> eyre::Result<()> {
+    for day in days {
+        let date = day.day().date();
+        for task in day.tasks() {
+            let start = time(date, task.start());
+            if !task.is_billable() {
+                writeln!(writer, "; billable: false")?;
+            }
+            if task.tags().is_empty() {
+                writeln!(writer, "i {} {}", start, task.description())?;
+            } else {
+                let account = task.tags().join(":");
+                writeln!(writer, "i {} {}  {}", start, account, task.description())?;
+            }
+            if let Some(end) = task.end() {
+                writeln!(writer, "o {}", time(date, end))?;
+            }
+        }
+    }
+    writer
+        .flush()
+        .wrap_err("could not write the timeclock export")
+}
+
+fn time(date: NaiveDate, time: NaiveTime) -> String {
+    let time = if time == END_OF_DAY {
+        LAST_SECOND
+    } else {
+        time
+    };
+    NaiveDateTime::new(date, time)
+        .format("%Y/%m/%d %H:%M:%S")
+        .to_string()
+}
@@ -0,0 +1,51 @@
+use std::io::Write;
+
+use chrono::{NaiveDate, NaiveTime};
+use eyre::Context;
+
+use crate::task::DayWithTasks;
+
+use super::utc;
+
+pub fn export(mut writer: impl Write, days: &[DayWithTasks]) -> eyre::Result<()> {
+    for day in days {
+        let date = day.day().date();
+        for task in day.tasks() {
+            let mut line = format!("inc {}", time(date, task.start())?);
+            if let Some(end) = task.end() {
+                line.push_str(&format!(" - {}", time(date, end)?));
+            }
+            let tags: Vec<_> = task.tags().iter().map(|tag| quote(tag)).collect();
+            match task.tags().first() {
+                Some(tag) if tag == task.description() => {
+                    line.push_str(&format!(" # {}", tags.join(" ")));
+                }
+                Some(_) => line.push_str(&format!(
+                    " # {} # {}",
+                    tags.join(" "),
+                    quote(task.description())
+                )),
+                None => line.push_str(&format!(" # # {}", quote(task.description()))),
+            }
+            writeln!(writer, "{}", line)?;
+        }
+    }
+    writer
+        .flush()
+        .wrap_err("could not write the timewarrior export")
+}
+
+fn time(date: NaiveDate, time: NaiveTime) -> eyre::Result<String> {
+    Ok(utc(date, time)?.format("%Y%m%dT%H%M%SZ").to_string())
+}
+
+fn quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|char| char.is_alphanumeric() || "-_.:".contains(char))
+    {
+        return value.to_owned();
+    }
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
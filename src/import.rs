@@ -0,0 +1,306 @@
+use std::{
+    fs,
+    io::BufRead,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use eyre::{eyre, Context};
+
+use crate::{
+    day::{DayRepository, END_OF_DAY},
+    task::TaskRepository,
+};
+
+pub use self::ical::holidays;
+
+mod ical;
+mod timeclock;
+mod timewarrior;
+
+pub enum ImportFormat {
+    Timewarrior,
+    Timeclock,
+}
+
+pub struct Interval {
+    start: NaiveDateTime,
+    end: Option<NaiveDateTime>,
+    description: String,
+    tags: Vec<String>,
+    billable: bool,
+}
+
+pub struct Imported {
+    tasks: usize,
+    skipped: Vec<String>,
+}
+
+impl ImportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Timewarrior => "data",
+            Self::Timeclock => "timeclock",
+        }
+    }
+}
+
+impl FromStr for ImportFormat {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "timewarrior" | "timew" => Ok(Self::Timewarrior),
+            "timeclock" | "ledger" => Ok(Self::Timeclock),
+            _ => Err(eyre!("the import format is not supported: {}", s)),
+        }
+    }
+}
+
+impl Interval {
+    pub fn new(
+        start: NaiveDateTime,
+        end: Option<NaiveDateTime>,
+        description: String,
+        tags: Vec<String>,
+    ) -> Self {
+        Self {
+            start,
+            end,
+            description,
+            tags,
+            billable: true,
+        }
+    }
+
+    pub fn with_billable(mut self, billable: bool) -> Self {
+        self.billable = billable;
+        self
+    }
+
+    fn segments(&self) -> Vec<(NaiveDate, NaiveTime, Option<NaiveTime>)> {
+        let Some(end) = self.end else {
+            return vec![(self.start.date(), self.start.time(), None)];
+        };
+        self.start
+            .date()
+            .iter_days()
+            .take_while(|date| *date <= end.date())
+            .map(|date| {
+                let start = if date == self.start.date() {
+                    self.start.time()
+                } else {
+                    NaiveTime::MIN
+                };
+                let end = if date == end.date() {
+                    end.time()
+                } else {
+                    END_OF_DAY
+                };
+                (date, start, Some(end))
+            })
+            .filter(|(_, start, end)| end.is_some_and(|end| *start < end))
+            .collect()
+    }
+}
+
+impl Imported {
+    pub fn tasks(&self) -> usize {
+        self.tasks
+    }
+
+    pub fn skipped(&self) -> &[String] {
+        self.skipped.as_slice()
+    }
+}
+
+pub fn files(path: &Path, format: &ImportFormat) -> eyre::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = fs::read_dir(path)
+        .wrap_err("could not read the directory to import")
+        .with_context(|| path.display().to_string())?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .wrap_err("could not read the directory to import")
+        .with_context(|| path.display().to_string())?;
+    files.retain(|file| {
+        file.is_file()
+            && file
+                .extension()
+                .is_some_and(|extension| extension == format.extension())
+    });
+    files.sort();
+    Ok(files)
+}
+
+pub fn intervals(reader: impl BufRead, format: &ImportFormat) -> eyre::Result<Vec<Interval>> {
+    match format {
+        ImportFormat::Timewarrior => timewarrior::intervals(reader),
+        ImportFormat::Timeclock => timeclock::intervals(reader),
+    }
+}
+
+pub fn store(
+    days: &DayRepository,
+    tasks: &TaskRepository,
+    intervals: Vec<Interval>,
+) -> eyre::Result<Imported> {
+    let mut imported = Imported {
+        tasks: 0,
+        skipped: Vec::new(),
+    };
+    for interval in intervals {
+        for (date, start, end) in interval.segments() {
+            let day = days.lookup(date)?;
+            let description = interval.description.as_str();
+            let task = match end {
+                Some(end) => tasks.add(day, description, &interval.tags, start, end, false),
+                None => tasks.start_at(day, description, &interval.tags, start),
+            };
+            let task = match task {
+                Ok(task) if !interval.billable => tasks.set_billable(task, false),
+                task => task,
+            };
+            match task {
+                Ok(_) => imported.tasks += 1,
+                Err(error) => imported.skipped.push(format!(
+                    "{} {} {}: {}",
+                    date,
+                    start.format("%H:%M"),
+                    description,
+                    error.root_cause()
+                )),
+            }
+        }
+    }
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, NaiveDate, NaiveTime, TimeDelta};
+
+    use crate::{
+        database::open_in_memory_connection,
+        day::{DayRepository, END_OF_DAY},
+        export::{export, ExportFormat, ExportRows},
+        task::TaskRepository,
+    };
+
+    use super::{intervals, store, ImportFormat, Interval};
+
+    fn repositories() -> (DayRepository, TaskRepository) {
+        let connection = open_in_memory_connection().unwrap();
+        let days = DayRepository::new(connection.clone());
+        let tasks = TaskRepository::new(connection);
+        (days, tasks)
+    }
+
+    #[test]
+    fn open_interval_while_a_task_is_running() {
+        let (days, tasks) = repositories();
+        let today = days.lookup(Local::now().date_naive()).unwrap();
+        let running = tasks.start(today, "running", &[]).unwrap();
+
+        let yesterday = Local::now().date_naive() - TimeDelta::days(1);
+        let start = yesterday.and_time(NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+        let interval = Interval::new(start, None, "imported".to_owned(), Vec::new());
+        let imported = store(&days, &tasks, vec![interval]).unwrap();
+
+        assert_eq!(imported.tasks(), 0);
+        assert_eq!(imported.skipped().len(), 1);
+        assert!(imported.skipped()[0].contains("another task is already running"));
+        let current = tasks.running().unwrap().map(|task| task.id());
+        assert_eq!(current, Some(running.id()));
+        let yesterday = days.lookup(yesterday).unwrap();
+        assert!(tasks.day_with_tasks(yesterday).unwrap().is_empty());
+    }
+
+    #[test]
+    fn timeclock_round_trip() {
+        let (days, tasks) = repositories();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let at = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        let tags = ["acme".to_owned(), "backend".to_owned()];
+        let start = NaiveTime::from_hms_milli_opt(9, 0, 0, 250).unwrap();
+        tasks
+            .add(
+                days.lookup(date).unwrap(),
+                "review",
+                &tags,
+                start,
+                at(10, 30),
+                false,
+            )
+            .unwrap();
+        let deploy = tasks
+            .add(
+                days.lookup(date).unwrap(),
+                "deploy",
+                &[],
+                at(22, 0),
+                END_OF_DAY,
+                false,
+            )
+            .unwrap();
+        tasks.set_billable(deploy, false).unwrap();
+        let day = tasks.day_with_tasks(days.lookup(date).unwrap()).unwrap();
+        let mut output = Vec::new();
+        export(
+            &mut output,
+            ExportFormat::Timeclock,
+            ExportRows::Tasks,
+            "default",
+            &[day],
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output.clone()).unwrap(),
+            "i 2024/03/04 09:00:00 acme:backend  review\n\
+             o 2024/03/04 10:30:00\n\
+             ; billable: false\n\
+             i 2024/03/04 22:00:00 deploy\n\
+             o 2024/03/04 23:59:59\n"
+        );
+
+        let (days, tasks) = repositories();
+        let imported = intervals(output.as_slice(), &ImportFormat::Timeclock).unwrap();
+        let imported = store(&days, &tasks, imported).unwrap();
+        assert_eq!(imported.tasks(), 2);
+        let day = tasks.day_with_tasks(days.lookup(date).unwrap()).unwrap();
+        let tasks: Vec<_> = day
+            .tasks()
+            .map(|task| {
+                (
+                    task.description().to_owned(),
+                    task.tags().to_vec(),
+                    task.start(),
+                    task.end(),
+                    task.is_billable(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            tasks,
+            [
+                (
+                    "review".to_owned(),
+                    tags.to_vec(),
+                    at(9, 0),
+                    Some(at(10, 30)),
+                    true
+                ),
+                (
+                    "deploy".to_owned(),
+                    Vec::new(),
+                    at(22, 0),
+                    Some(END_OF_DAY),
+                    false
+                ),
+            ]
+        );
+    }
+}
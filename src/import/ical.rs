@@ -0,0 +1,260 @@
+use std::io::BufRead;
+
+use chrono::{Datelike, NaiveDate};
+use eyre::{eyre, Context};
+
+pub struct Holiday {
+    date: NaiveDate,
+    name: Option<String>,
+}
+
+pub struct Holidays {
+    holidays: Vec<Holiday>,
+    skipped: Vec<String>,
+}
+
+#[derive(Default)]
+struct Event {
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    summary: Option<String>,
+    rule: Option<String>,
+    exceptions: Vec<NaiveDate>,
+}
+
+struct Recurrence {
+    interval: i32,
+    count: Option<usize>,
+    until: Option<NaiveDate>,
+}
+
+impl Holiday {
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl Holidays {
+    pub fn holidays(&self) -> &[Holiday] {
+        self.holidays.as_slice()
+    }
+
+    pub fn skipped(&self) -> &[String] {
+        self.skipped.as_slice()
+    }
+}
+
+pub fn holidays(reader: impl BufRead, until: NaiveDate) -> eyre::Result<Holidays> {
+    let mut holidays = Holidays {
+        holidays: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let mut event: Option<Event> = None;
+    for line in unfold(reader)? {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, parameters) = name.split_once(';').unwrap_or((name, ""));
+        match (name.to_ascii_uppercase().as_str(), &mut event) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                event = Some(Event::default());
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                let event = event.take().unwrap();
+                match event.starts(until)? {
+                    Some(starts) => holidays.holidays.extend(event.holidays(starts)),
+                    None => holidays.skipped.push(format!(
+                        "{} {}: the recurrence is not supported: {}",
+                        event.start.unwrap_or_default(),
+                        event.summary.as_deref().unwrap_or_default(),
+                        event.rule.as_deref().unwrap_or_default()
+                    )),
+                }
+            }
+            ("DTSTART", Some(event)) => event.start = Some(date(value, parameters)?),
+            ("DTEND", Some(event)) => event.end = Some(date(value, parameters)?),
+            ("SUMMARY", Some(event)) => event.summary = Some(unescape(value)),
+            ("RRULE", Some(event)) => event.rule = Some(value.trim().to_owned()),
+            ("EXDATE", Some(event)) => {
+                for value in value.split(',') {
+                    event.exceptions.push(date(value, parameters)?);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(holidays)
+}
+
+impl Event {
+    fn starts(&self, until: NaiveDate) -> eyre::Result<Option<Vec<NaiveDate>>> {
+        let start = self
+            .start
+            .ok_or_else(|| eyre!("the event has no start: {:?}", self.summary))?;
+        let Some(rule) = &self.rule else {
+            return Ok(Some(vec![start]));
+        };
+        let Some(recurrence) = Recurrence::parse(rule, start) else {
+            return Ok(None);
+        };
+        let starts = recurrence
+            .starts(start, until)
+            .into_iter()
+            .filter(|start| !self.exceptions.contains(start))
+            .collect();
+        Ok(Some(starts))
+    }
+
+    fn holidays(&self, starts: Vec<NaiveDate>) -> Vec<Holiday> {
+        let Some(first) = self.start else {
+            return Vec::new();
+        };
+        let days = self
+            .end
+            .filter(|end| *end > first)
+            .map_or(1, |end| (end - first).num_days());
+        starts
+            .into_iter()
+            .flat_map(|start| start.iter_days().take(days as usize))
+            .map(|date| Holiday {
+                date,
+                name: self.summary.clone(),
+            })
+            .collect()
+    }
+}
+
+impl Recurrence {
+    fn parse(rule: &str, start: NaiveDate) -> Option<Self> {
+        let mut yearly = false;
+        let mut recurrence = Self {
+            interval: 1,
+            count: None,
+            until: None,
+        };
+        for part in rule.split(';') {
+            let (name, value) = part.split_once('=')?;
+            match name.to_ascii_uppercase().as_str() {
+                "FREQ" => yearly = value.eq_ignore_ascii_case("YEARLY"),
+                "INTERVAL" => recurrence.interval = value.parse().ok().filter(|n| *n > 0)?,
+                "COUNT" => recurrence.count = Some(value.parse().ok()?),
+                "UNTIL" => recurrence.until = Some(date(value, "").ok()?),
+                "BYMONTH" if value.parse() == Ok(start.month()) => {}
+                "BYMONTHDAY" if value.parse() == Ok(start.day()) => {}
+                "WKST" => {}
+                _ => return None,
+            }
+        }
+        yearly.then_some(recurrence)
+    }
+
+    fn starts(&self, start: NaiveDate, until: NaiveDate) -> Vec<NaiveDate> {
+        let until = match (self.until, self.count) {
+            (Some(end), _) => end,
+            (None, Some(_)) => NaiveDate::MAX,
+            (None, None) => until,
+        };
+        let mut starts = Vec::new();
+        let mut year = start.year();
+        while year <= until.year() && self.count.is_none_or(|count| starts.len() < count) {
+            if let Some(date) = start.with_year(year).filter(|date| *date <= until) {
+                starts.push(date);
+            }
+            year += self.interval;
+        }
+        starts
+    }
+}
+
+fn unfold(reader: impl BufRead) -> eyre::Result<Vec<String>> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in reader.lines() {
+        let line = line.wrap_err("could not read the calendar")?;
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+    Ok(lines)
+}
+
+fn date(value: &str, parameters: &str) -> eyre::Result<NaiveDate> {
+    let value = value.trim();
+    let date = value.get(..8).unwrap_or(value);
+    NaiveDate::parse_from_str(date, "%Y%m%d")
+        .wrap_err("could not parse the date of the event")
+        .with_context(|| format!("{};{}", value, parameters))
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+        .trim()
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::holidays;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn recurring_events() {
+        let calendar = "BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20241225\r
+DTEND;VALUE=DATE:20241227\r
+RRULE:FREQ=YEARLY;BYMONTH=12;BYMONTHDAY=25\r
+EXDATE;VALUE=DATE:20251225\r
+SUMMARY:Christmas\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20240229\r
+RRULE:FREQ=YEARLY;COUNT=2\r
+SUMMARY:Leap day\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20241128\r
+RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=4TH\r
+SUMMARY:Thanksgiving\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+        let holidays = holidays(calendar.as_bytes(), date(2026, 12, 31)).unwrap();
+        let dates: Vec<_> = holidays
+            .holidays()
+            .iter()
+            .map(|holiday| (holiday.date(), holiday.name().unwrap()))
+            .collect();
+        assert_eq!(
+            dates,
+            [
+                (date(2024, 12, 25), "Christmas"),
+                (date(2024, 12, 26), "Christmas"),
+                (date(2026, 12, 25), "Christmas"),
+                (date(2026, 12, 26), "Christmas"),
+                (date(2024, 2, 29), "Leap day"),
+                (date(2028, 2, 29), "Leap day"),
+            ]
+        );
+        assert_eq!(
+            holidays.skipped(),
+            ["2024-11-28 Thanksgiving: the recurrence is not supported: \
+              FREQ=YEARLY;BYMONTH=11;BYDAY=4TH"]
+        );
+    }
+}
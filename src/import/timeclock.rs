@@ -0,0 +1,105 @@
+use std::io::BufRead;
+
+use chrono::NaiveDateTime;
+use eyre::{eyre, Context};
+
+use crate::{day::END_OF_DAY, export::LAST_SECOND};
+
+use super::Interval;
+
+struct ClockIn {
+    start: NaiveDateTime,
+    description: String,
+    tags: Vec<String>,
+    billable: bool,
+}
+
+pub fn intervals(reader: impl BufRead) -> eyre::Result<Vec<Interval>> {
+    let mut intervals = Vec::new();
+    let mut clock_in: Option<ClockIn> = None;
+    let mut billable = true;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.wrap_err("could not read the timeclock data")?;
+        let entry = entry(&line, &mut clock_in, &mut billable, &mut intervals)
+            .wrap_err_with(|| format!("could not parse line {} of the timeclock data", index + 1))
+            .with_context(|| line.clone());
+        entry?;
+    }
+    if let Some(open) = clock_in {
+        intervals.push(
+            Interval::new(open.start, None, open.description, open.tags)
+                .with_billable(open.billable),
+        );
+    }
+    Ok(intervals)
+}
+
+fn entry(
+    line: &str,
+    clock_in: &mut Option<ClockIn>,
+    billable: &mut bool,
+    intervals: &mut Vec<Interval>,
+) -> eyre::Result<()> {
+    let line = line.trim_end();
+    let Some((code, rest)) = line.split_once(' ') else {
+        return Ok(());
+    };
+    match code {
+        ";" if rest.trim() == "billable: false" => *billable = false,
+        "i" => {
+            if clock_in.is_some() {
+                return Err(eyre!("clocked in before clocking out"));
+            }
+            let (time, rest) = time(rest)?;
+            let (account, description) = match rest.split_once("  ") {
+                Some((account, description)) => (account.trim(), description.trim()),
+                None => match rest.split_once('\t') {
+                    Some((account, description)) => (account.trim(), description.trim()),
+                    None => (rest.trim(), ""),
+                },
+            };
+            let (description, tags) = if description.is_empty() {
+                (account.to_owned(), Vec::new())
+            } else {
+                let tags = account.split(':').map(str::to_owned).collect();
+                (description.to_owned(), tags)
+            };
+            *clock_in = Some(ClockIn {
+                start: time,
+                description,
+                tags,
+                billable: std::mem::replace(billable, true),
+            });
+        }
+        "o" | "O" => {
+            let open = clock_in
+                .take()
+                .ok_or_else(|| eyre!("clocked out before clocking in"))?;
+            let (time, _) = time(rest)?;
+            let time = match time.time() == LAST_SECOND {
+                true => time.date().and_time(END_OF_DAY),
+                false => time,
+            };
+            intervals.push(
+                Interval::new(open.start, Some(time), open.description, open.tags)
+                    .with_billable(open.billable),
+            );
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn time(value: &str) -> eyre::Result<(NaiveDateTime, &str)> {
+    let value = value.trim_start();
+    let mut parts = value.splitn(3, ' ');
+    let date = parts.next().unwrap_or_default();
+    let time = parts.next().unwrap_or_default();
+    let rest = parts.next().unwrap_or_default();
+    let datetime = format!("{} {}", date, time);
+    let datetime = NaiveDateTime::parse_from_str(&datetime, "%Y/%m/%d %H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(&datetime, "%Y-%m-%d %H:%M:%S%.f"))
+        .wrap_err("could not parse the time of the entry")
+        .with_context(|| datetime.clone())?;
+    Ok((datetime, rest))
+}
@@ -0,0 +1,74 @@
+use std::io::BufRead;
+
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use eyre::{eyre, Context};
+
+use super::Interval;
+
+pub fn intervals(reader: impl BufRead) -> eyre::Result<Vec<Interval>> {
+    let mut intervals = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.wrap_err("could not read the timewarrior data")?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let interval = interval(line)
+            .wrap_err_with(|| format!("could not parse line {} of the timewarrior data", index + 1))
+            .with_context(|| line.to_owned())?;
+        intervals.push(interval);
+    }
+    Ok(intervals)
+}
+
+fn interval(line: &str) -> eyre::Result<Interval> {
+    let line = line
+        .strip_prefix("inc ")
+        .ok_or_else(|| eyre!("an interval starts with inc"))?;
+    let (range, rest) = line.split_once(" #").unwrap_or((line, ""));
+    let (start, end) = match range.trim().split_once(" - ") {
+        Some((start, end)) => (start, Some(end)),
+        None => (range.trim(), None),
+    };
+    let start = time(start)?;
+    let end = end.map(time).transpose()?;
+    let (tags, annotation) = match rest.split_once(" # ") {
+        Some((tags, annotation)) => (tags, Some(annotation)),
+        None => (rest, None),
+    };
+    let tags = words(tags);
+    let description = annotation
+        .map(words)
+        .map(|words| words.join(" "))
+        .filter(|annotation| !annotation.is_empty())
+        .or_else(|| tags.first().cloned())
+        .unwrap_or_else(|| "timewarrior".to_owned());
+    Ok(Interval::new(start, end, description, tags))
+}
+
+fn time(value: &str) -> eyre::Result<NaiveDateTime> {
+    let time = NaiveDateTime::parse_from_str(value.trim(), "%Y%m%dT%H%M%SZ")
+        .wrap_err("could not parse the time of the interval")
+        .with_context(|| value.to_owned())?;
+    Ok(Utc
+        .from_utc_datetime(&time)
+        .with_timezone(&Local)
+        .naive_local())
+}
+
+fn words(value: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    let mut chars = value.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' if quoted => word.extend(chars.next()),
+            '"' => quoted = !quoted,
+            ' ' if !quoted => words.extend((!word.is_empty()).then(|| std::mem::take(&mut word))),
+            _ => word.push(char),
+        }
+    }
+    words.extend((!word.is_empty()).then_some(word));
+    words
+}
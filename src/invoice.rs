@@ -0,0 +1,246 @@
+use std::{io::Write, str::FromStr};
+
+use chrono::{NaiveDate, TimeDelta};
+use eyre::eyre;
+
+use crate::{
+    config::Rate,
+    task::{DayWithTasks, RoundingScope},
+};
+
+mod html;
+mod json;
+mod markdown;
+
+pub enum InvoiceFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+pub struct Invoice {
+    client: String,
+    from: NaiveDate,
+    to: NaiveDate,
+    rate: f64,
+    currency: String,
+    lines: Vec<InvoiceLine>,
+}
+
+pub struct InvoiceLine {
+    description: String,
+    delta: TimeDelta,
+    rounded: Option<TimeDelta>,
+}
+
+impl FromStr for InvoiceFormat {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            "json" => Ok(Self::Json),
+            _ => Err(eyre!("the invoice format is not supported: {}", s)),
+        }
+    }
+}
+
+impl Invoice {
+    pub fn new(
+        client: String,
+        from: NaiveDate,
+        to: NaiveDate,
+        rate: &Rate,
+        days: Vec<DayWithTasks>,
+    ) -> Self {
+        let mut lines: Vec<InvoiceLine> = Vec::new();
+        for day in days {
+            let day = day.filter_closed();
+            let mut groups: Vec<_> = day
+                .task_groups()
+                .into_iter()
+                .map(|group| {
+                    let rounded = day
+                        .rounding()
+                        .map(|rounding| group.rounded(rounding).unwrap_or(group.delta()));
+                    (group, rounded)
+                })
+                .collect();
+            if day.rounding().map(|rounding| rounding.per()) == Some(RoundingScope::Day) {
+                let adjustment = day.rounded().unwrap_or(day.delta()) - day.delta();
+                if let Some((_, Some(rounded))) =
+                    groups.iter_mut().max_by_key(|(group, _)| group.delta())
+                {
+                    *rounded += adjustment;
+                }
+            }
+            for (group, rounded) in groups {
+                match lines
+                    .iter_mut()
+                    .find(|line| line.description == group.description())
+                {
+                    Some(line) => {
+                        line.delta += group.delta();
+                        line.rounded = line.rounded.zip(rounded).map(|(line, group)| line + group);
+                    }
+                    None => lines.push(InvoiceLine {
+                        description: group.description().to_owned(),
+                        delta: group.delta(),
+                        rounded,
+                    }),
+                }
+            }
+        }
+        Self {
+            client,
+            from,
+            to,
+            rate: rate.rate,
+            currency: rate.currency.clone(),
+            lines,
+        }
+    }
+
+    pub fn client(&self) -> &str {
+        self.client.as_str()
+    }
+
+    pub fn from(&self) -> NaiveDate {
+        self.from
+    }
+
+    pub fn to(&self) -> NaiveDate {
+        self.to
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    pub fn currency(&self) -> &str {
+        self.currency.as_str()
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &InvoiceLine> {
+        self.lines.iter()
+    }
+
+    pub fn hours(&self) -> f64 {
+        self.lines.iter().map(|line| line.hours()).sum()
+    }
+
+    pub fn rounded_hours(&self) -> Option<f64> {
+        self.lines.iter().map(|line| line.rounded_hours()).sum()
+    }
+
+    pub fn is_rounded(&self) -> bool {
+        self.lines.iter().any(|line| line.rounded.is_some())
+    }
+
+    pub fn amount(&self) -> f64 {
+        self.lines.iter().map(|line| line.amount(self.rate)).sum()
+    }
+}
+
+impl InvoiceLine {
+    pub fn description(&self) -> &str {
+        self.description.as_str()
+    }
+
+    pub fn hours(&self) -> f64 {
+        hours(self.delta)
+    }
+
+    pub fn rounded_hours(&self) -> Option<f64> {
+        self.rounded.map(hours)
+    }
+
+    pub fn amount(&self, rate: f64) -> f64 {
+        round_cents(self.rounded_hours().unwrap_or(self.hours()) * rate)
+    }
+}
+
+pub fn write_invoice(
+    writer: impl Write,
+    format: InvoiceFormat,
+    invoice: &Invoice,
+) -> eyre::Result<()> {
+    match format {
+        InvoiceFormat::Markdown => markdown::write(writer, invoice),
+        InvoiceFormat::Html => html::write(writer, invoice),
+        InvoiceFormat::Json => json::write(writer, invoice),
+    }
+}
+
+fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+fn hours(delta: TimeDelta) -> f64 {
+    delta.num_seconds() as f64 / 3600.0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::{NaiveDate, NaiveTime, TimeDelta};
+
+    use crate::{
+        config::Rate,
+        database::open_in_memory_connection,
+        day::DayRepository,
+        task::{DayWithTasks, Rounding, TaskRepository},
+    };
+
+    use super::Invoice;
+
+    fn day(rounding: &str) -> DayWithTasks {
+        let connection = open_in_memory_connection().unwrap();
+        let days = DayRepository::new(connection.clone());
+        let tasks = TaskRepository::new(connection);
+        let date = NaiveDate::from_ymd_opt(2024, 3, 6).unwrap();
+        let at = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        let day = days.lookup(date).unwrap();
+        tasks
+            .add(day, "review", &[], at(9, 0), at(9, 20), false)
+            .unwrap();
+        let day = days.lookup(date).unwrap();
+        tasks
+            .add(day, "docs", &[], at(10, 0), at(10, 20), false)
+            .unwrap();
+        let day = days.lookup(date).unwrap();
+        let rounding = Rounding::from_str(rounding).unwrap();
+        tasks
+            .day_with_tasks(day)
+            .unwrap()
+            .with_rounding(Some(rounding))
+    }
+
+    fn invoice(day: DayWithTasks) -> Invoice {
+        let rate = Rate {
+            rate: 100.0,
+            currency: "EUR".to_owned(),
+        };
+        let date = day.day().date();
+        Invoice::new("acme".to_owned(), date, date, &rate, vec![day])
+    }
+
+    #[test]
+    fn day_and_group_rounding_differ() {
+        let group = day("up:15:group");
+        let per_day = day("up:15:day");
+        assert_eq!(group.rounded(), Some(TimeDelta::minutes(60)));
+        assert_eq!(per_day.rounded(), Some(TimeDelta::minutes(45)));
+
+        let group = invoice(group);
+        let per_day = invoice(per_day);
+        assert_eq!(group.rounded_hours(), Some(1.0));
+        assert_eq!(group.amount(), 100.0);
+        assert_eq!(per_day.rounded_hours(), Some(0.75));
+        assert_eq!(per_day.amount(), 75.0);
+        let hours: Vec<_> = per_day.lines().map(|line| line.hours()).collect();
+        assert_eq!(hours.iter().sum::<f64>(), per_day.hours());
+    }
+}
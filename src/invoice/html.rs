@@ -0,0 +1,71 @@
+use std::io::Write;
+
+use eyre::Context;
+
+use super::Invoice;
+
+pub fn write(mut writer: impl Write, invoice: &Invoice) -> eyre::Result<()> {
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(writer, "<html>")?;
+    writeln!(writer, "<head>")?;
+    writeln!(writer, "<meta charset=\"utf-8\">")?;
+    writeln!(
+        writer,
+        "<title>Invoice {}</title>",
+        escape(invoice.client())
+    )?;
+    writeln!(writer, "</head>")?;
+    writeln!(writer, "<body>")?;
+    writeln!(writer, "<h1>Invoice {}</h1>", escape(invoice.client()))?;
+    writeln!(writer, "<p>{} - {}</p>", invoice.from(), invoice.to())?;
+    writeln!(writer, "<table>")?;
+    writeln!(
+        writer,
+        "<tr><th>Description</th><th>Hours</th>{}<th>Rate</th><th>Amount</th></tr>",
+        if invoice.is_rounded() {
+            "<th>Rounded</th>"
+        } else {
+            ""
+        }
+    )?;
+    for line in invoice.lines() {
+        writeln!(
+            writer,
+            "<tr><td>{}</td><td>{:.2}</td>{}<td>{:.2} {}</td><td>{:.2} {}</td></tr>",
+            escape(line.description()),
+            line.hours(),
+            rounded(line.rounded_hours(), "td"),
+            invoice.rate(),
+            escape(invoice.currency()),
+            line.amount(invoice.rate()),
+            escape(invoice.currency())
+        )?;
+    }
+    writeln!(
+        writer,
+        "<tr><th>Total</th><th>{:.2}</th>{}<th></th><th>{:.2} {}</th></tr>",
+        invoice.hours(),
+        rounded(invoice.rounded_hours(), "th"),
+        invoice.amount(),
+        escape(invoice.currency())
+    )?;
+    writeln!(writer, "</table>")?;
+    writeln!(writer, "</body>")?;
+    writeln!(writer, "</html>")?;
+    writer.flush().wrap_err("could not write the invoice")
+}
+
+fn rounded(hours: Option<f64>, cell: &str) -> String {
+    match hours {
+        Some(hours) => format!("<{}>{:.2}</{}>", cell, hours, cell),
+        None => String::new(),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
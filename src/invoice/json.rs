@@ -0,0 +1,55 @@
+use std::io::Write;
+
+use chrono::NaiveDate;
+use eyre::Context;
+use serde::Serialize;
+
+use super::Invoice;
+
+#[derive(Serialize)]
+struct InvoiceJson<'a> {
+    client: &'a str,
+    from: NaiveDate,
+    to: NaiveDate,
+    currency: &'a str,
+    rate: f64,
+    hours: f64,
+    rounded_hours: Option<f64>,
+    amount: f64,
+    lines: Vec<LineJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct LineJson<'a> {
+    description: &'a str,
+    hours: f64,
+    rounded_hours: Option<f64>,
+    rate: f64,
+    amount: f64,
+}
+
+pub fn write(mut writer: impl Write, invoice: &Invoice) -> eyre::Result<()> {
+    let json = InvoiceJson {
+        client: invoice.client(),
+        from: invoice.from(),
+        to: invoice.to(),
+        currency: invoice.currency(),
+        rate: invoice.rate(),
+        hours: invoice.hours(),
+        rounded_hours: invoice.rounded_hours(),
+        amount: invoice.amount(),
+        lines: invoice
+            .lines()
+            .map(|line| LineJson {
+                description: line.description(),
+                hours: line.hours(),
+                rounded_hours: line.rounded_hours(),
+                rate: invoice.rate(),
+                amount: line.amount(invoice.rate()),
+            })
+            .collect(),
+    };
+    serde_json::to_writer_pretty(&mut writer, &json).wrap_err("could not write the invoice")?;
+    writeln!(writer)?;
+    Ok(())
+}
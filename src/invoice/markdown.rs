@@ -0,0 +1,49 @@
+use std::io::Write;
+
+use eyre::Context;
+
+use super::Invoice;
+
+pub fn write(mut writer: impl Write, invoice: &Invoice) -> eyre::Result<()> {
+    writeln!(writer, "# Invoice {}", invoice.client())?;
+    writeln!(writer)?;
+    writeln!(writer, "{} - {}", invoice.from(), invoice.to())?;
+    writeln!(writer)?;
+    if invoice.is_rounded() {
+        writeln!(writer, "| Description | Hours | Rounded | Rate | Amount |")?;
+        writeln!(writer, "| --- | ---: | ---: | ---: | ---: |")?;
+    } else {
+        writeln!(writer, "| Description | Hours | Rate | Amount |")?;
+        writeln!(writer, "| --- | ---: | ---: | ---: |")?;
+    }
+    for line in invoice.lines() {
+        writeln!(
+            writer,
+            "| {} | {:.2} |{} {:.2} {} | {:.2} {} |",
+            line.description().replace('|', "\\|"),
+            line.hours(),
+            rounded(line.rounded_hours(), false),
+            invoice.rate(),
+            invoice.currency(),
+            line.amount(invoice.rate()),
+            invoice.currency()
+        )?;
+    }
+    writeln!(
+        writer,
+        "| **Total** | **{:.2}** |{} | **{:.2} {}** |",
+        invoice.hours(),
+        rounded(invoice.rounded_hours(), true),
+        invoice.amount(),
+        invoice.currency()
+    )?;
+    writer.flush().wrap_err("could not write the invoice")
+}
+
+fn rounded(hours: Option<f64>, bold: bool) -> String {
+    match (hours, bold) {
+        (Some(hours), false) => format!(" {:.2} |", hours),
+        (Some(hours), true) => format!(" **{:.2}** |", hours),
+        (None, _) => String::new(),
+    }
+}
@@ -1,30 +1,51 @@
 #![allow(unused)]
 
+use std::fs::File;
+use std::io::{stdout, BufReader};
+use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::str::FromStr;
 
-use chrono::{Days, Local, TimeDelta, Timelike};
-use clap::{Arg, ArgAction, Command};
+use chrono::{Datelike, Days, Local, NaiveDate, TimeDelta, Timelike, Weekday};
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use config::Config;
-use database::open_database_connection;
-use day::DayRepository;
-use eyre::{eyre, ContextCompat};
+use database::{open_database_connection, open_read_only_connection};
+use day::{Day, DayKind, DayRepository};
+use eyre::{eyre, Context, ContextCompat};
+use itertools::Itertools;
+use someutil::NaiveWeekExt;
 use termfmt::{TermFmtExt, TermFmtsExt};
 
-use crate::task::TaskRepository;
+use crate::task::{Balance, DayWithTasks, Report, Rounding, Task, TaskRepository};
 
+use self::backup::{Backup, RestoreMode};
+use self::completion::Shell;
+use self::daemon::{Client, Request};
+use self::export::{export, ExportFormat, ExportRows};
+use self::import::ImportFormat;
+use self::invoice::{write_invoice, Invoice, InvoiceFormat};
 use self::output::{DataBundle, OutputFmt};
-use self::time::TimeOrDelta;
+use self::status::{write_status, Status, StatusFormat};
+use self::time::{DateExpression, DateRange, TimeOrDelta};
 
+mod backup;
+mod completion;
 mod config;
+mod daemon;
 mod database;
 mod day;
+mod export;
+mod import;
+mod invoice;
 mod output;
+mod status;
 mod task;
 mod time;
 
+const COMPLETIONS: usize = 50;
+
 fn main() -> eyre::Result<()> {
-    let cli = Command::new("ttrack")
+    let cli_command = Command::new("ttrack")
         .subcommands([
             Command::new("start")
                 .args([
@@ -37,15 +58,61 @@ fn main() -> eyre::Result<()> {
                         .short('t')
                         .num_args(1..)
                         .help("tags of the task (may be used to associate projects)"),
+                    non_billable_arg(),
+                    fuzzy_arg(),
                 ])
                 .about("start a new task, if another task is running it will get stopped"),
+            Command::new("add")
+                .args([
+                    Arg::new("description")
+                        .num_args(1)
+                        .required(true)
+                        .help("name of the task"),
+                    Arg::new("start")
+                        .num_args(1)
+                        .required(true)
+                        .allow_negative_numbers(true)
+                        .help("start time of the task (e.g. 0900 or -90 for 90 minutes ago)"),
+                    Arg::new("end")
+                        .num_args(1)
+                        .required(true)
+                        .allow_negative_numbers(true)
+                        .help("end time of the task (e.g. 1030 or +90 for 90 minutes after the start)"),
+                    Arg::new("day")
+                        .long("day")
+                        .short('d')
+                        .allow_negative_numbers(true)
+                        .default_value("today")
+                        .help("day of the task (e.g. -1, 2026-10-16, friday or \"last friday\")"),
+                    Arg::new("tags")
+                        .long("tags")
+                        .short('t')
+                        .num_args(1..)
+                        .help("tags of the task (may be used to associate projects)"),
+                    Arg::new("trim")
+                        .long("trim")
+                        .action(ArgAction::SetTrue)
+                        .help("trim overlapping tasks instead of refusing to add the task"),
+                    non_billable_arg(),
+                ])
+                .about("add a task that was already done"),
             Command::new("stop").about("stop the currently running task"),
-            Command::new("rename")
+            Command::new("pause").about("pause the currently running task, continue it with resume"),
+            Command::new("resume")
                 .arg(
+                    Arg::new("id")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(u64))
+                        .help("id of the task to continue, defaults to the last stopped task"),
+                )
+                .about("start a new task with the description and tags of a stopped task"),
+            Command::new("rename")
+                .args([
                     Arg::new("description")
                         .num_args(1)
                         .help("the new name of the currently running task"),
-                )
+                    fuzzy_arg(),
+                ])
                 .about("rename the current task."),
             Command::new("restart")
                 .arg(
@@ -58,6 +125,10 @@ fn main() -> eyre::Result<()> {
                 .about("rename the current task."),
             Command::new("edit")
                 .args([
+                    Arg::new("id")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(u64))
+                        .help("id of the task, defaults to the currently running task"),
                     Arg::new("name")
                         .long("name")
                         .short('n')
@@ -67,19 +138,111 @@ fn main() -> eyre::Result<()> {
                         .short('t')
                         .num_args(1..)
                         .help("new tags of the task (replaces all old ones)"),
+                    Arg::new("start")
+                        .long("start")
+                        .short('s')
+                        .allow_negative_numbers(true)
+                        .help("new start time of the task (e.g. 0930, -10 or +10)"),
+                    Arg::new("end")
+                        .long("end")
+                        .short('e')
+                        .allow_negative_numbers(true)
+                        .help("new end time of the task (e.g. 1730, -10 or +10)"),
+                    Arg::new("billable")
+                        .long("billable")
+                        .short('b')
+                        .value_parser(clap::value_parser!(bool))
+                        .help("whether the task is billed to the client (true or false)"),
+                ])
+                .about("edit the currently running task or the task with the given id"),
+            Command::new("delete")
+                .arg(
+                    Arg::new("id")
+                        .num_args(1)
+                        .required(true)
+                        .value_parser(clap::value_parser!(u64))
+                        .help("id of the task"),
+                )
+                .about("delete the task with the given id"),
+            Command::new("undo")
+                .arg(
+                    Arg::new("count")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("1")
+                        .help("number of operations to undo"),
+                )
+                .about("undo the last changes to the tasks"),
+            Command::new("export")
+                .args([
+                    Arg::new("range")
+                        .num_args(1)
+                        .required(true)
+                        .allow_hyphen_values(true)
+                        .help("the days to export (e.g. 2026-10-01..2026-10-31 or monday..)"),
+                    Arg::new("format")
+                        .long("format")
+                        .short('f')
+                        .default_value("csv")
+                        .help("format of the export (csv, timewarrior, timeclock or ics)"),
+                    Arg::new("groups")
+                        .long("groups")
+                        .action(ArgAction::SetTrue)
+                        .help("export one row per task description and day instead of one per task"),
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .help("file to write the export to, defaults to stdout"),
+                    tags_filter_arg(),
+                ])
+                .about("export the tasks of the days in the range"),
+            Command::new("import")
+                .args([
+                    Arg::new("files")
+                        .num_args(1..)
+                        .required(true)
+                        .help("files or directories to import (e.g. ~/.timewarrior/data)"),
+                    Arg::new("format")
+                        .long("format")
+                        .short('f')
+                        .default_value("timewarrior")
+                        .help("format of the files (timewarrior or timeclock)"),
                 ])
-                .about("edit the currently running task"),
+                .about("import tasks from other time trackers, overlapping tasks are skipped"),
+            Command::new("backup")
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .help("file to write the backup to, defaults to stdout"),
+                )
+                .about("back up every day, task, tag and note as one json document"),
+            Command::new("restore")
+                .args([
+                    Arg::new("file").required(true).help("the backup to restore"),
+                    Arg::new("mode")
+                        .long("mode")
+                        .short('m')
+                        .default_value("merge")
+                        .help("merge the backup into the tasks or replace all of them and the undo history (merge or replace)"),
+                ])
+                .about("restore a backup, duplicate tasks are skipped"),
             Command::new("get").about("get the currently running task"),
-            Command::new("today").about("list the tasks of today"),
-            Command::new("yesterday").about("list the task of yesterday"),
+            Command::new("today")
+                .arg(tags_filter_arg())
+                .about("list the tasks of today"),
+            Command::new("yesterday")
+                .arg(tags_filter_arg())
+                .about("list the task of yesterday"),
             Command::new("day")
                 .arg(
                     Arg::new("days")
                         .num_args(1)
                         .allow_negative_numbers(true)
-                        .default_value("0")
-                        .help("number of days to go back"),
+                        .default_value("today")
+                        .help("the day (e.g. -2, 2026-10-16, friday or \"last friday\")"),
                 )
+                .arg(tags_filter_arg())
                 .about("list the task of the day"),
             Command::new("week")
                 .arg(
@@ -87,32 +250,250 @@ fn main() -> eyre::Result<()> {
                         .num_args(1)
                         .allow_negative_numbers(true)
                         .default_value("0")
-                        .help("number of weeks to go back"),
+                        .help("the week as an offset from this week or a day of the week (e.g. -2, 1, 2026-10-16 or friday)"),
                 )
+                .arg(tags_filter_arg())
                 .about("list the task of the week"),
+            Command::new("month")
+                .arg(
+                    Arg::new("months")
+                        .num_args(1)
+                        .allow_negative_numbers(true)
+                        .value_parser(clap::value_parser!(i32))
+                        .default_value("0")
+                        .help("the month as an offset from this month (e.g. -1 for the last month)"),
+                )
+                .arg(tags_filter_arg())
+                .about("report the totals of the month"),
+            Command::new("year")
+                .arg(
+                    Arg::new("years")
+                        .num_args(1)
+                        .allow_negative_numbers(true)
+                        .value_parser(clap::value_parser!(i32))
+                        .default_value("0")
+                        .help("the year as an offset from this year (e.g. -1 for the last year)"),
+                )
+                .arg(tags_filter_arg())
+                .about("report the totals of the year"),
+            Command::new("range")
+                .arg(
+                    Arg::new("range")
+                        .num_args(1)
+                        .required(true)
+                        .allow_hyphen_values(true)
+                        .help("the days to list (e.g. 2026-10-01..2026-10-15, monday.. or -10..-3)"),
+                )
+                .arg(tags_filter_arg())
+                .about("list the tasks of every day in the range"),
+            Command::new("balance")
+                .args([
+                    Arg::new("since")
+                        .long("since")
+                        .short('s')
+                        .allow_negative_numbers(true)
+                        .help("first day of the balance, defaults to the configured start or the first tracked day"),
+                    Arg::new("until")
+                        .long("until")
+                        .short('u')
+                        .allow_negative_numbers(true)
+                        .default_value("today")
+                        .help("last day of the balance"),
+                ])
+                .about("sum up the overtime or undertime against the working hours"),
+            Command::new("mark")
+                .args([
+                    Arg::new("range")
+                        .num_args(1)
+                        .required(true)
+                        .allow_hyphen_values(true)
+                        .help("the days to mark (e.g. 2026-10-16, 2026-12-24..2026-12-31 or -1)"),
+                    Arg::new("kind")
+                        .num_args(1)
+                        .required(true)
+                        .help("kind of the days (workday, vacation, sick, holiday or weekend)"),
+                    Arg::new("note")
+                        .long("note")
+                        .short('n')
+                        .help("note of the days (e.g. the name of the holiday)"),
+                ])
+                .about("mark days as vacation, sick leave, holiday or weekend, these count as fulfilled"),
+            Command::new("holidays")
+                .arg(
+                    Arg::new("file")
+                        .num_args(1)
+                        .required(true)
+                        .help("iCalendar file with the public holidays"),
+                )
+                .about("mark the public holidays of an iCalendar file as holidays"),
+            Command::new("invoice")
+                .args([
+                    Arg::new("client")
+                        .long("client")
+                        .short('c')
+                        .required(true)
+                        .help("tag of the client, the rate of the tag is used"),
+                    Arg::new("range")
+                        .long("range")
+                        .short('r')
+                        .required(true)
+                        .allow_hyphen_values(true)
+                        .help("the days to bill (e.g. 2026-10-01..2026-10-31)"),
+                    Arg::new("format")
+                        .long("format")
+                        .short('f')
+                        .default_value("markdown")
+                        .help("format of the invoice (markdown, html or json)"),
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .help("file to write the invoice to, defaults to stdout"),
+                ])
+                .about("summarize the billable tasks of a client as line items"),
+            Command::new("completions")
+                .arg(
+                    Arg::new("shell")
+                        .num_args(1)
+                        .required(true)
+                        .help("the shell to complete (bash, zsh or fish)"),
+                )
+                .about("print the shell completions"),
+            Command::new("complete")
+                .arg(
+                    Arg::new("values")
+                        .num_args(1)
+                        .required(true)
+                        .value_parser(["descriptions", "tags", "tasks"]),
+                )
+                .hide(true)
+                .about("list the values used by the shell completions"),
+            Command::new("daemon").about(
+                "serve the tasks over a unix socket, the other commands use it while it is running",
+            ),
+            Command::new("status")
+                .args([
+                    Arg::new("template")
+                        .long("template")
+                        .default_value("{description} {elapsed}")
+                        .help("template of the running task ({description}, {tags}, {start}, {elapsed} and {today})"),
+                    Arg::new("idle")
+                        .long("idle")
+                        .default_value("")
+                        .help("template when no task is running (e.g. \"idle {today}\")"),
+                    Arg::new("waybar")
+                        .long("waybar")
+                        .action(ArgAction::SetTrue)
+                        .help("print waybar json with the class active or idle"),
+                    Arg::new("watch")
+                        .long("watch")
+                        .action(ArgAction::SetTrue)
+                        .help("print the status again every minute"),
+                ])
+                .about("print the running task for status bars"),
             Command::new("is_active").about("exit successfully if a task is currently running"),
+            Command::new("profile")
+                .subcommands([
+                    Command::new("list").about("list the configured profiles"),
+                    Command::new("use")
+                        .arg(
+                            Arg::new("name")
+                                .num_args(1)
+                                .required(true)
+                                .help("name of the profile"),
+                        )
+                        .about("use the profile by default"),
+                ])
+                .subcommand_required(true)
+                .about("manage the profiles, each profile has its own database"),
+            Command::new("config")
+                .subcommand(
+                    Command::new("show").about("show the settings and where they come from"),
+                )
+                .subcommand_required(true)
+                .about("inspect the configuration"),
         ])
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .global(true)
+                .help("path of the config file (toml or json)"),
+        )
+        .arg(
+            Arg::new("round")
+                .long("round")
+                .global(true)
+                .help("round the durations of reports and exports (e.g. up:15, nearest:15:group, down:30:day or none)"),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .short('p')
+                .global(true)
+                .help("name of the profile to use"),
+        )
         .about("track the time you spend on projects or other tasks")
         .subcommand_required(true)
-        .termfmts()
-        .get_matches();
+        .termfmts();
+    let cli = cli_command.clone().get_matches();
+
+    let config_file = cli.get_one::<String>("config").map(PathBuf::from);
+    let profile = cli.get_one::<String>("profile").map(String::as_str);
+    let rounding = cli
+        .get_one::<String>("round")
+        .map(|rounding| Rounding::from_str(rounding))
+        .transpose()?;
+    let config = Config::load(config_file.as_deref(), profile, rounding)?;
+    let mut term = cli.termfmt(DataBundle::default());
+
+    let subcommand = cli.subcommand().unwrap();
+    if let ("complete", command) = subcommand {
+        return complete(command, &config);
+    }
+    if let Some(mut client) = Client::connect(&config)? {
+        if via_daemon(&mut client, subcommand, &config, &mut term)? {
+            return Ok(());
+        }
+    }
 
-    let config = Config::load()?;
     let connection = open_database_connection(&config)?;
 
-    let mut term = cli.termfmt(DataBundle::default());
+    let day_repository = DayRepository::new(connection.clone());
+    let task_repository = TaskRepository::new(connection.clone());
 
-    let day_repository = DayRepository::new(connection.clone())?;
-    let task_repository = TaskRepository::new(connection)?;
+    let transaction = if is_journaled(subcommand.0) {
+        task_repository.begin(subcommand.0);
+        let transaction = task_repository.transaction()?;
+        if subcommand.0 != "restore" {
+            task_repository.carry_over(Local::now().date_naive())?;
+        }
+        Some(transaction)
+    } else {
+        None
+    };
 
-    match cli.subcommand().unwrap() {
+    match subcommand {
         ("start", command) => {
-            let description: &String = command.get_one("description").unwrap();
+            let description = description_arg(command, &task_repository)?;
+            let tags = tags_or_default(command, &config);
             let today = day_repository.today()?;
-            let task = task_repository.start(today, description.as_str())?;
+            let task = task_repository.start(today, description.as_str(), &tags)?;
+            let task = billable_or_default(command, &task_repository, task)?;
             term.task(task);
         }
-        ("stop", _) => {
+        ("add", command) => {
+            let description: &String = command.get_one("description").unwrap();
+            let tags = tags_or_default(command, &config);
+            let date = date_arg(command, "day")?;
+            let start = time_arg(command, "start")?.unwrap();
+            let start = start.resolve(Local::now().time());
+            let end = time_arg(command, "end")?.unwrap().resolve(start);
+            let trim = command.get_flag("trim");
+            let day = day_repository.lookup(date)?;
+            let task = task_repository.add(day, description, &tags, start, end, trim)?;
+            let task = billable_or_default(command, &task_repository, task)?;
+            term.task(task);
+        }
+        ("stop" | "pause", _) => {
             let today = day_repository.today()?;
             let Ok(task) = task_repository.stop(today) else {
                 term.error("no task is started yet!");
@@ -121,15 +502,36 @@ fn main() -> eyre::Result<()> {
             };
             term.task(task);
         }
+        ("resume", command) => {
+            let today = day_repository.today()?;
+            let task = match command.get_one::<u64>("id") {
+                Some(id) => task_repository.task(*id)?,
+                None => {
+                    if let Ok(task) = task_repository.current(today) {
+                        return Err(eyre!("the task is still running: {}", task));
+                    }
+                    task_repository
+                        .last_stopped()?
+                        .wrap_err("there is no stopped task to resume")?
+                }
+            };
+            let billable = task.is_billable();
+            let task = task_repository.start(today, task.description(), task.tags())?;
+            let task = match billable {
+                true => task,
+                false => task_repository.set_billable(task, false)?,
+            };
+            term.task(task);
+        }
         ("rename", command) => {
-            let description: &String = command.get_one("description").unwrap();
+            let description = description_arg(command, &task_repository)?;
             let today = day_repository.today()?;
-            let task = task_repository.rename_current(today, description)?;
+            let task = task_repository.rename_current(today, &description)?;
             term.task(task);
         }
         ("restart", command) => {
             let time: &String = command.get_one("time").unwrap();
-            let time_or_delta = TimeOrDelta::from_str(&time)?;
+            let time_or_delta = TimeOrDelta::from_str(time)?;
             let day = day_repository.today()?;
             let task = task_repository.current(day)?;
             let task = match time_or_delta {
@@ -138,56 +540,438 @@ fn main() -> eyre::Result<()> {
             }?;
             term.task(task);
         }
-        ("today", _) => {
+        ("edit", command) => {
+            let task = match command.get_one::<u64>("id") {
+                Some(id) => {
+                    let task = task_repository.task(*id)?;
+                    let day = day_repository.day(task.day())?;
+                    task_repository.with_day(task, day)?
+                }
+                None => task_repository.current(day_repository.today()?)?,
+            };
+            let start = time_arg(command, "start")?.map(|start| start.resolve(task.start()));
+            let end = time_arg(command, "end")?.map(|end| end.resolve(task.end_or_day_time()));
+            let range_start = start.unwrap_or(task.start());
+            if let Some(range_end) = end.or(task.end()) {
+                if range_start >= range_end {
+                    return Err(eyre!(
+                        "cannot set start time after end time: {} >= {}",
+                        range_start,
+                        range_end
+                    ));
+                }
+            }
+            let task = match command.get_one::<String>("name") {
+                Some(description) => task_repository.rename_task(task, description)?,
+                None => task,
+            };
+            let task = if command.contains_id("tags") {
+                task_repository.set_tags(task, tags_arg(command))?
+            } else {
+                task
+            };
+            let task = match command.get_one::<bool>("billable") {
+                Some(billable) => task_repository.set_billable(task, *billable)?,
+                None => task,
+            };
+            let task = task_repository.set_range(task, start, end)?;
+            term.task(task);
+        }
+        ("delete", command) => {
+            let id: &u64 = command.get_one("id").unwrap();
+            let task = task_repository.delete(*id)?;
+            term.info(format_args!("deleted {}", task));
+        }
+        ("undo", command) => {
+            let count: &usize = command.get_one("count").unwrap();
+            let operations = task_repository.undo(*count)?;
+            if operations.is_empty() {
+                term.error("there is nothing to undo!");
+            }
+            for operation in operations {
+                term.info(format_args!(
+                    "undone {} from {}",
+                    operation.name(),
+                    operation.created().format("%Y-%m-%d %H:%M")
+                ));
+            }
+        }
+        ("today", command) => {
             let Ok(today) = day_repository.today() else {
                 term.error("could not find or create day!");
                 term.end();
                 return Ok(());
             };
-            let tasks_for_day = task_repository.day_with_tasks(today)?;
-            term.day_with_tasks(tasks_for_day);
+            let tasks_for_day = day_with_tasks(&day_repository, &task_repository, &config, today)?;
+            term.day_with_tasks(tasks_for_day.filter_tags(&tags_arg(command)));
         }
-        ("yesterday", _) => {
+        ("yesterday", command) => {
             let Ok(yesterday) = day_repository.yesterday().map_err(|error| {
                 term.error(format_args!("could not find or create day: {}", error));
                 term.end();
             }) else {
                 return Ok(());
             };
-            let tasks_for_day = task_repository.day_with_tasks(yesterday)?;
-            term.day_with_tasks(tasks_for_day);
+            let tasks_for_day =
+                day_with_tasks(&day_repository, &task_repository, &config, yesterday)?;
+            term.day_with_tasks(tasks_for_day.filter_tags(&tags_arg(command)));
         }
         ("day", command) => {
-            let days: &String = command.get_one("days").unwrap();
-            let days = i32::from_str(days)?;
-            let date = Local::now()
-                .date_naive()
-                .checked_sub_days(Days::new(days.abs() as u64))
-                .wrap_err("cannot sub days")?;
-            let day = day_repository.from_date(date)?;
-            let day_with_tasks = task_repository.day_with_tasks(day)?;
-            term.day_with_tasks(day_with_tasks);
+            let date = date_arg(command, "days")?;
+            let day = day_repository.lookup(date)?;
+            let day_with_tasks = day_with_tasks(&day_repository, &task_repository, &config, day)?;
+            term.day_with_tasks(day_with_tasks.filter_tags(&tags_arg(command)));
         }
         ("week", command) => {
             let weeks: &String = command.get_one("weeks").unwrap();
-            let weeks = i32::from_str(weeks)?;
-            let week = if weeks == 0 {
+            let today = Local::now().date_naive();
+            let date = match DateExpression::from_str(weeks)? {
+                DateExpression::Offset(weeks) => DateExpression::Offset(weeks * 7).resolve(today),
+                expression => expression.resolve(today),
+            }?;
+            let week = if date.week(Weekday::Mon).days().contains(&today) {
                 day_repository.week_till_today()?
             } else {
-                let weeks = weeks.abs() as u64;
-                let today = Local::now().date_naive();
-                let date = today
-                    .checked_sub_days(Days::new(weeks * 7))
-                    .unwrap_or(today);
                 day_repository.complete_week(date)?
             };
+            let tags = tags_arg(command);
             let week = week
                 .into_iter()
-                .filter_map(|day| task_repository.day_with_tasks(day).ok());
+                .filter_map(|day| {
+                    day_with_tasks(&day_repository, &task_repository, &config, day).ok()
+                })
+                .map(|day_with_tasks| day_with_tasks.filter_tags(&tags));
             for day_with_tasks in week {
                 term.day_with_tasks(day_with_tasks);
             }
         }
+        ("month", command) => {
+            let months: &i32 = command.get_one("months").unwrap();
+            let (from, to) = time::month(Local::now().date_naive(), *months)?;
+            let name = "Month".to_owned();
+            let report = report(
+                &day_repository,
+                &task_repository,
+                &config,
+                name,
+                from,
+                to,
+                command,
+            )?;
+            term.report(report);
+        }
+        ("year", command) => {
+            let years: &i32 = command.get_one("years").unwrap();
+            let (from, to) = time::year(Local::now().date_naive(), *years)?;
+            let name = "Year".to_owned();
+            let report = report(
+                &day_repository,
+                &task_repository,
+                &config,
+                name,
+                from,
+                to,
+                command,
+            )?;
+            term.report(report);
+        }
+        ("range", command) => {
+            let range: &String = command.get_one("range").unwrap();
+            let (from, to) = DateRange::from_str(range)?.resolve(Local::now().date_naive())?;
+            let tags = tags_arg(command);
+            for day in day_repository.lookup_range(from, to)? {
+                let day_with_tasks =
+                    day_with_tasks(&day_repository, &task_repository, &config, day)?;
+                term.day_with_tasks(day_with_tasks.filter_tags(&tags));
+            }
+        }
+        ("export", command) => {
+            let range: &String = command.get_one("range").unwrap();
+            let (from, to) = DateRange::from_str(range)?.resolve(Local::now().date_naive())?;
+            let format: &String = command.get_one("format").unwrap();
+            let format = ExportFormat::from_str(format)?;
+            let rows = if command.get_flag("groups") {
+                ExportRows::Groups
+            } else {
+                ExportRows::Tasks
+            };
+            let tags = tags_arg(command);
+            let days = day_repository
+                .lookup_range(from, to)?
+                .into_iter()
+                .map(|day| day_with_tasks(&day_repository, &task_repository, &config, day))
+                .map_ok(|day_with_tasks| day_with_tasks.filter_tags(&tags))
+                .collect::<eyre::Result<Vec<_>>>()?;
+            match command.get_one::<String>("output") {
+                Some(path) => {
+                    let file = File::create(path)
+                        .wrap_err("could not create the export file")
+                        .with_context(|| path.to_owned())?;
+                    export(file, format, rows, config.profile().name(), &days)?;
+                }
+                None => export(
+                    stdout().lock(),
+                    format,
+                    rows,
+                    config.profile().name(),
+                    &days,
+                )?,
+            }
+        }
+        ("balance", command) => {
+            let today = Local::now().date_naive();
+            let since = match command.get_one::<String>("since") {
+                Some(since) => DateExpression::from_str(since)?.resolve(today)?,
+                None => match config.profile().since() {
+                    Some(since) => *since.value(),
+                    None => day_repository
+                        .first_day()?
+                        .map(|day| day.date())
+                        .unwrap_or(today),
+                },
+            };
+            let until = date_arg(command, "until")?;
+            if since > until {
+                return Err(eyre!("the balance starts after {}", until));
+            }
+            let days = day_repository
+                .lookup_range(since, until)?
+                .into_iter()
+                .map(|day| day_with_tasks(&day_repository, &task_repository, &config, day))
+                .collect::<eyre::Result<Vec<_>>>()?;
+            term.balance(Balance::new(since, until, &days));
+        }
+        ("mark", command) => {
+            let range: &String = command.get_one("range").unwrap();
+            let (from, to) = DateRange::from_str(range)?.resolve(Local::now().date_naive())?;
+            let kind: &String = command.get_one("kind").unwrap();
+            let kind = DayKind::from_str(kind)?;
+            let note = command.get_one::<String>("note").map(String::as_str);
+            for day in day_repository.lookup_range(from, to)? {
+                let day = day_repository.set_kind(day, kind)?;
+                let day = day_repository.set_note(day, note)?;
+                term.info(format_args!("marked {} as {}", day.date(), day.kind()));
+            }
+        }
+        ("holidays", command) => {
+            let path: &String = command.get_one("file").unwrap();
+            let file = File::open(path)
+                .wrap_err("could not open the calendar")
+                .with_context(|| path.to_owned())?;
+            let today = Local::now().date_naive();
+            let until = NaiveDate::from_ymd_opt(today.year() + 1, 12, 31).unwrap();
+            let holidays = import::holidays(BufReader::new(file), until)?;
+            for skipped in holidays.skipped() {
+                term.error(format_args!("skipped {}", skipped));
+            }
+            for holiday in holidays.holidays() {
+                let day = day_repository.lookup(holiday.date())?;
+                let day = day_repository.set_kind(day, DayKind::Holiday)?;
+                day_repository.set_note(day, holiday.name())?;
+                term.info(format_args!(
+                    "marked {} as holiday {}",
+                    holiday.date(),
+                    holiday.name().unwrap_or_default()
+                ));
+            }
+        }
+        ("import", command) => {
+            let format: &String = command.get_one("format").unwrap();
+            let format = ImportFormat::from_str(format)?;
+            let mut intervals = Vec::new();
+            for path in command.get_many::<String>("files").unwrap() {
+                for file in import::files(Path::new(path), &format)? {
+                    let reader = File::open(&file)
+                        .map(BufReader::new)
+                        .wrap_err("could not open the file to import")
+                        .with_context(|| file.display().to_string())?;
+                    intervals.extend(import::intervals(reader, &format)?);
+                }
+            }
+            let imported = import::store(&day_repository, &task_repository, intervals)?;
+            task_repository.carry_over(Local::now().date_naive())?;
+            for skipped in imported.skipped() {
+                term.error(format_args!("skipped {}", skipped));
+            }
+            term.info(format_args!("imported {} tasks", imported.tasks()));
+        }
+        ("backup", command) => {
+            let backup = Backup::create(&day_repository, &task_repository)?;
+            match command.get_one::<String>("output") {
+                Some(path) => {
+                    let file = File::create(path)
+                        .wrap_err("could not create the backup file")
+                        .with_context(|| path.to_owned())?;
+                    backup.write(file)?;
+                }
+                None => backup.write(stdout().lock())?,
+            }
+        }
+        ("restore", command) => {
+            let path: &String = command.get_one("file").unwrap();
+            let mode: &String = command.get_one("mode").unwrap();
+            let mode = RestoreMode::from_str(mode)?;
+            let reader = File::open(path)
+                .map(BufReader::new)
+                .wrap_err("could not open the backup")
+                .with_context(|| path.to_owned())?;
+            let restored =
+                Backup::read(reader)?.restore(&day_repository, &task_repository, mode)?;
+            task_repository.carry_over(Local::now().date_naive())?;
+            for skipped in restored.skipped() {
+                term.error(format_args!("skipped {}", skipped));
+            }
+            term.info(format_args!(
+                "restored {} tasks of {} days, skipped {} duplicates",
+                restored.tasks(),
+                restored.days(),
+                restored.duplicates()
+            ));
+        }
+        ("invoice", command) => {
+            let client: &String = command.get_one("client").unwrap();
+            let client = client.trim().to_ascii_lowercase();
+            let tags = vec![client.clone()];
+            let range: &String = command.get_one("range").unwrap();
+            let (from, to) = DateRange::from_str(range)?.resolve(Local::now().date_naive())?;
+            let format: &String = command.get_one("format").unwrap();
+            let format = InvoiceFormat::from_str(format)?;
+            let rate = config
+                .profile()
+                .rate(&client)
+                .wrap_err_with(|| format!("there is no rate configured for the tag {}", client))?;
+            let days = day_repository
+                .lookup_range(from, to)?
+                .into_iter()
+                .map(|day| day_with_tasks(&day_repository, &task_repository, &config, day))
+                .map_ok(|day_with_tasks| day_with_tasks.filter_tags(&tags).filter_billable())
+                .collect::<eyre::Result<Vec<_>>>()?;
+            let invoice = Invoice::new(client, from, to, rate, days);
+            match command.get_one::<String>("output") {
+                Some(path) => {
+                    let file = File::create(path)
+                        .wrap_err("could not create the invoice file")
+                        .with_context(|| path.to_owned())?;
+                    write_invoice(file, format, &invoice)?;
+                }
+                None => write_invoice(stdout().lock(), format, &invoice)?,
+            }
+        }
+        ("completions", command) => {
+            let shell: &String = command.get_one("shell").unwrap();
+            let shell = Shell::from_str(shell)?;
+            print!("{}", completion::script(shell, &cli_command));
+        }
+        ("daemon", _) => {
+            let path = config.socket_path();
+            let listener = daemon::bind(&path)?;
+            term.info(format_args!("listening on {}", path.display()));
+            term.end();
+            return daemon::serve(listener, &connection, &day_repository, &task_repository);
+        }
+        ("status", command) => {
+            let template: &String = command.get_one("template").unwrap();
+            let idle: &String = command.get_one("idle").unwrap();
+            let format = if command.get_flag("waybar") {
+                StatusFormat::Waybar
+            } else {
+                StatusFormat::Text
+            };
+            if command.get_flag("watch") {
+                return status::watch(&day_repository, &task_repository, &format, template, idle);
+            }
+            let today = day_repository.today()?;
+            let status = Status::new(&task_repository.day_with_tasks(today)?);
+            write_status(stdout().lock(), &format, &status, template, idle)?;
+            return Ok(());
+        }
+        ("config", command) => match command.subcommand() {
+            Some(("show", _)) => {
+                match config.file() {
+                    Some(file) => term.info(format_args!("config file: {}", file.display())),
+                    None => term.info("config file: none"),
+                }
+                let path = config.path();
+                term.info(format_args!(
+                    "path = {} (from {})",
+                    path.value().display(),
+                    path.source()
+                ));
+                let profile = config.active_profile();
+                term.info(format_args!(
+                    "profile = {} (from {})",
+                    profile.value(),
+                    profile.source()
+                ));
+                let profile = config.profile();
+                let database = profile.database();
+                term.info(format_args!(
+                    "database = {} (from {})",
+                    database.value().display(),
+                    database.source()
+                ));
+                let tags = profile.tags();
+                term.info(format_args!(
+                    "tags = {} (from {})",
+                    tags.value().join(", "),
+                    tags.source()
+                ));
+                if let Some(hours) = profile.hours() {
+                    term.info(format_args!(
+                        "hours = {} (from {})",
+                        hours.value(),
+                        hours.source()
+                    ));
+                }
+                if let Some(since) = profile.since() {
+                    term.info(format_args!(
+                        "since = {} (from {})",
+                        since.value(),
+                        since.source()
+                    ));
+                }
+                let rates = profile.rates();
+                for (tag, rate) in rates.value() {
+                    term.info(format_args!(
+                        "rate {} = {} (from {})",
+                        tag,
+                        rate,
+                        rates.source()
+                    ));
+                }
+                if let Some(rounding) = config.rounding() {
+                    term.info(format_args!(
+                        "rounding = {} (from {})",
+                        rounding.value(),
+                        rounding.source()
+                    ));
+                }
+            }
+            _ => term.error("the config command is not implemented."),
+        },
+        ("profile", command) => match command.subcommand() {
+            Some(("list", _)) => {
+                for profile in config.profiles() {
+                    let marker = if profile.name() == config.profile().name() {
+                        "*"
+                    } else {
+                        " "
+                    };
+                    term.info(format_args!(
+                        "{} {} ({})",
+                        marker,
+                        profile.name(),
+                        profile.database().value().display()
+                    ));
+                }
+            }
+            Some(("use", command)) => {
+                let name: &String = command.get_one("name").unwrap();
+                config.use_profile(name)?;
+                term.info(format_args!("using the profile {} by default", name));
+            }
+            _ => term.error("the profile command is not implemented."),
+        },
         ("get", _) => {
             let Ok(today) = day_repository.today() else {
                 term.error("could not get todays day!");
@@ -206,6 +990,7 @@ fn main() -> eyre::Result<()> {
             };
             let Ok(task) = task_repository.current(today) else {
                 term.error("no task is currently active");
+                term.end();
                 exit(1);
             };
             term.task(task);
@@ -213,7 +998,220 @@ fn main() -> eyre::Result<()> {
         (command, _) => term.error(eyre!("the command {} is not implemented.", command)),
     }
 
-    term.flush();
+    if let Some(transaction) = transaction {
+        transaction.commit()?;
+    }
     term.end();
     Ok(())
 }
+
+fn tags_filter_arg() -> Arg {
+    Arg::new("tags")
+        .long("tags")
+        .short('t')
+        .num_args(1..)
+        .help("only list tasks with at least one of the tags")
+}
+
+fn non_billable_arg() -> Arg {
+    Arg::new("non_billable")
+        .long("non-billable")
+        .action(ArgAction::SetTrue)
+        .help("do not bill the task to the client")
+}
+
+fn billable_or_default(
+    command: &ArgMatches,
+    task_repository: &TaskRepository,
+    task: Task<Day>,
+) -> eyre::Result<Task<Day>> {
+    if !command.get_flag("non_billable") {
+        return Ok(task);
+    }
+    task_repository.set_billable(task, false)
+}
+
+fn fuzzy_arg() -> Arg {
+    Arg::new("fuzzy")
+        .long("fuzzy")
+        .short('f')
+        .action(ArgAction::SetTrue)
+        .help("match the description to the description of an earlier task")
+}
+
+fn description_arg(command: &ArgMatches, task_repository: &TaskRepository) -> eyre::Result<String> {
+    let description: &String = command.get_one("description").unwrap();
+    if !command.get_flag("fuzzy") {
+        return Ok(description.to_owned());
+    }
+    task_repository.match_description(description)
+}
+
+fn tags_arg(command: &ArgMatches) -> Vec<String> {
+    command
+        .get_many::<String>("tags")
+        .map(|tags| tags.map(|tag| tag.trim().to_ascii_lowercase()).collect())
+        .unwrap_or_default()
+}
+
+fn time_arg(command: &ArgMatches, id: &str) -> eyre::Result<Option<TimeOrDelta>> {
+    command
+        .get_one::<String>(id)
+        .map(|time| TimeOrDelta::from_str(time))
+        .transpose()
+}
+
+fn is_journaled(command: &str) -> bool {
+    matches!(
+        command,
+        "start"
+            | "add"
+            | "import"
+            | "restore"
+            | "stop"
+            | "pause"
+            | "resume"
+            | "rename"
+            | "restart"
+            | "edit"
+            | "delete"
+    )
+}
+
+fn date_arg(command: &ArgMatches, id: &str) -> eyre::Result<NaiveDate> {
+    let date: &String = command.get_one(id).unwrap();
+    DateExpression::from_str(date)?.resolve(Local::now().date_naive())
+}
+
+fn report(
+    day_repository: &DayRepository,
+    task_repository: &TaskRepository,
+    config: &Config,
+    name: String,
+    from: NaiveDate,
+    to: NaiveDate,
+    command: &ArgMatches,
+) -> eyre::Result<Report> {
+    let tags = tags_arg(command);
+    let days = day_repository
+        .lookup_range(from, to)?
+        .into_iter()
+        .map(|day| day_with_tasks(day_repository, task_repository, config, day))
+        .map_ok(|day_with_tasks| day_with_tasks.filter_tags(&tags))
+        .collect::<eyre::Result<_>>()?;
+    Ok(Report::new(name, from, to, days))
+}
+
+fn day_with_tasks(
+    day_repository: &DayRepository,
+    task_repository: &TaskRepository,
+    config: &Config,
+    day: Day,
+) -> eyre::Result<DayWithTasks> {
+    let note = day_repository.note(&day)?;
+    let day_with_tasks = task_repository.day_with_tasks(day)?.with_note(note);
+    Ok(with_profile(config, day_with_tasks))
+}
+
+fn with_profile(config: &Config, day_with_tasks: DayWithTasks) -> DayWithTasks {
+    let day = *day_with_tasks.day();
+    let expected = if day.kind().is_fulfilled() {
+        TimeDelta::zero()
+    } else {
+        config.profile().target(day.date().weekday())
+    };
+    let rounding = config.rounding().map(|rounding| *rounding.value());
+    day_with_tasks
+        .with_expected(expected)
+        .with_rounding(rounding)
+}
+
+fn via_daemon(
+    client: &mut Client,
+    subcommand: (&str, &ArgMatches),
+    config: &Config,
+    term: &mut impl OutputFmt,
+) -> eyre::Result<bool> {
+    match subcommand {
+        ("start", command) => {
+            let description: &String = command.get_one("description").unwrap();
+            let request = Request::Start {
+                description: description.to_owned(),
+                tags: tags_or_default(command, config),
+                fuzzy: command.get_flag("fuzzy"),
+                billable: !command.get_flag("non_billable"),
+            };
+            if let Some(task) = client.request(&request)?.task() {
+                term.task(task);
+            }
+        }
+        ("stop" | "pause", _) => match client.request(&Request::Stop)?.task() {
+            Some(task) => term.task(task),
+            None => term.error("no task is started yet!"),
+        },
+        ("get", _) => {
+            if let Some(task) = client.request(&Request::Current)?.task() {
+                term.task(task);
+            }
+        }
+        ("today", command) => {
+            let day = client
+                .request(&Request::Day { date: None })?
+                .day()
+                .wrap_err("the daemon did not send the day")?;
+            term.day_with_tasks(with_profile(config, day).filter_tags(&tags_arg(command)));
+        }
+        ("status", command) if !command.get_flag("watch") => {
+            let template: &String = command.get_one("template").unwrap();
+            let idle: &String = command.get_one("idle").unwrap();
+            let format = if command.get_flag("waybar") {
+                StatusFormat::Waybar
+            } else {
+                StatusFormat::Text
+            };
+            let day = client
+                .request(&Request::Day { date: None })?
+                .day()
+                .wrap_err("the daemon did not send the day")?;
+            let status = Status::new(&day);
+            write_status(stdout().lock(), &format, &status, template, idle)?;
+            return Ok(true);
+        }
+        _ => return Ok(false),
+    }
+    term.end();
+    Ok(true)
+}
+
+fn complete(command: &ArgMatches, config: &Config) -> eyre::Result<()> {
+    let Some(connection) = open_read_only_connection(config)? else {
+        return Ok(());
+    };
+    let task_repository = TaskRepository::new(connection);
+    let values: &String = command.get_one("values").unwrap();
+    match values.as_str() {
+        "descriptions" => {
+            for description in task_repository.recent_descriptions(COMPLETIONS)? {
+                println!("{}", description);
+            }
+        }
+        "tags" => {
+            for tag in task_repository.recent_tags(COMPLETIONS)? {
+                println!("{}", tag);
+            }
+        }
+        _ => {
+            for task in task_repository.recent_tasks(COMPLETIONS)? {
+                println!("{}\t{}", task.id(), task.description());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn tags_or_default(command: &ArgMatches, config: &Config) -> Vec<String> {
+    if command.contains_id("tags") {
+        return tags_arg(command);
+    }
+    config.profile().tags().value().clone()
+}
@@ -1,47 +1,62 @@
 use std::{
     fmt::Display,
-    io::{stdout, IsTerminal},
+    io::{self, stdout, IsTerminal},
 };
 
+use chrono::TimeDelta;
 use serde::Serialize;
 use termfmt::{
     chrono::{DateFmt, DeltaFmt, TimeFmt},
-    termarrow, termarrow_fg, termerr, termh1, termh2, termprefix1, termprefix2, BundleFmt, DataFmt,
-    Fg, TermFmt, TermStyle,
+    termarrow, termarrow_fg, termerr, termh1, termh2, terminfo, termprefix1, termprefix2,
+    termprefix3, BundleFmt, DataFmt, Fg, TermFmt, TermStyle,
 };
 
 use crate::{
     day::Day,
-    task::{DayWithTasks, Task, TaskGroup},
+    task::{Balance, DayWithTasks, Report, RoundingScope, Task, TaskGroup, Total},
 };
 
+use self::json::{BalanceJson, DayJson, ReportJson, TaskJson, JSON_VERSION};
+
+mod csv;
+mod json;
+
 pub enum OutputData {
+    Info(String),
     Error(String),
     Task(Task<Day>),
     DayWithTask(DayWithTasks),
+    Report(Report),
+    Balance(Balance),
     End,
 }
 
-#[derive(Default, Serialize)]
+#[derive(Serialize)]
 pub struct DataBundle {
-    #[serde(rename = "output", skip_serializing_if = "Vec::is_empty")]
-    tasks: Vec<Task<Day>>,
-    #[serde(rename = "output", skip_serializing_if = "Vec::is_empty")]
-    day_with_tasks: Vec<DayWithTasks>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    version: u32,
+    tasks: Vec<TaskJson>,
+    days: Vec<DayJson>,
+    reports: Vec<ReportJson>,
+    balances: Vec<BalanceJson>,
     info: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    error: Vec<String>,
+    errors: Vec<String>,
 }
 
 pub trait OutputFmt {
+    fn info(&mut self, value: impl Display);
     fn error(&mut self, value: impl Display);
     fn day_with_tasks(&mut self, value: DayWithTasks);
     fn task(&mut self, task: Task<Day>);
+    fn report(&mut self, report: Report);
+    fn balance(&mut self, balance: Balance);
     fn end(&mut self);
 }
 
 impl OutputFmt for TermFmt<OutputData, DataBundle> {
+    fn info(&mut self, value: impl Display) {
+        self.output(OutputData::Info(format!("{}", value)));
+    }
+
     fn error(&mut self, value: impl Display) {
         self.output(OutputData::Error(format!("{}", value)));
     }
@@ -54,32 +69,77 @@ impl OutputFmt for TermFmt<OutputData, DataBundle> {
         self.output(OutputData::Task(value));
     }
 
+    fn report(&mut self, value: Report) {
+        self.output(OutputData::Report(value));
+    }
+
+    fn balance(&mut self, value: Balance) {
+        self.output(OutputData::Balance(value));
+    }
+
     fn end(&mut self) {
         self.output(OutputData::End);
+        if let Err(error) = self.flush() {
+            termerr(error);
+        }
     }
 }
 
 impl DataFmt for OutputData {
     fn plain(self) {
         match self {
+            Self::Info(value) => println!("{}", value),
             Self::Error(value) => eprintln!("{}", value),
             Self::Task(value) => println!("{}", value),
             Self::DayWithTask(value) => {
-                println!("{}", value.day());
+                match value.note() {
+                    Some(note) => println!("{} note=\"{}\"", value.day(), note),
+                    None => println!("{}", value.day()),
+                }
                 for task in value.tasks() {
                     println!("{}", task);
                 }
             }
+            Self::Report(value) => {
+                println!(
+                    "{} {} {} {}",
+                    value.name(),
+                    value.from(),
+                    value.to(),
+                    RoundedFmt(value.delta(), value.rounded())
+                );
+                for total in value.days() {
+                    println!("day {} {}", total.name(), RoundedFmt::total(&total));
+                }
+                for total in value.weeks() {
+                    println!("week {} {}", total.name(), RoundedFmt::total(&total));
+                }
+                for total in value.tasks() {
+                    println!("task \"{}\" {}", total.name(), RoundedFmt::total(&total));
+                }
+            }
+            Self::Balance(value) => println!(
+                "balance {} {} expected={} actual={} difference={}",
+                value.from(),
+                value.to(),
+                DeltaFmt::new(value.expected()),
+                DeltaFmt::new(value.actual()),
+                SignedDeltaFmt(value.difference())
+            ),
             Self::End => {}
         }
     }
 
     fn interactive(self) {
         match self {
+            Self::Info(value) => terminfo(value),
             Self::Error(value) => termerr(value),
             Self::Task(value) => {
                 termprefix2("Task", value.description());
-                term_task_body(&value);
+                if !value.tags().is_empty() {
+                    termarrow(value.tags().join(", ").fg_bright_black());
+                }
+                term_task_body(&value, None);
             }
             Self::DayWithTask(value) => {
                 termprefix1(
@@ -87,56 +147,170 @@ impl DataFmt for OutputData {
                     format_args!(
                         "{} {}",
                         DateFmt::new(value.day().date()),
-                        format_args!("({})", DeltaFmt::new(value.delta())).fg_bright_black()
+                        format_args!(
+                            "({} of {}, {})",
+                            RoundedFmt(value.delta(), value.rounded()),
+                            DeltaFmt::new(value.expected()),
+                            SignedDeltaFmt(value.overtime())
+                        )
+                        .fg_bright_black()
                     ),
                 );
+                match (value.day().kind().is_fulfilled(), value.note()) {
+                    (true, Some(note)) => {
+                        termarrow(format_args!("{}: {}", value.day().kind(), note))
+                    }
+                    (true, None) => termarrow(value.day().kind()),
+                    (false, Some(note)) => termarrow(note.fg_bright_black()),
+                    (false, None) => {}
+                }
                 if value.is_empty() {
                     termarrow("no tasks recorded!".fg_bright_black());
                 }
                 for group in value.task_groups() {
+                    let rounded = value
+                        .rounding()
+                        .and_then(|rounding| group.rounded(rounding));
                     termprefix2(
                         "Task",
                         format_args!(
                             "{} {}",
                             group.description(),
-                            format_args!("({})", DeltaFmt::new(group.delta())).fg_bright_black()
+                            format_args!("({})", RoundedFmt(group.delta(), rounded))
+                                .fg_bright_black()
                         ),
                     );
                     for task in group.tasks() {
-                        term_task_body(task);
+                        let rounded = value
+                            .rounding()
+                            .filter(|rounding| rounding.per() == RoundingScope::Task)
+                            .map(|rounding| rounding.round(task.delta()));
+                        term_task_body(task, rounded);
                     }
                 }
+                for group in value.tag_groups() {
+                    termprefix3(
+                        "Tag",
+                        format_args!(
+                            "{} {}",
+                            group.tag(),
+                            format_args!("({})", DeltaFmt::new(group.delta())).fg_bright_black()
+                        ),
+                    );
+                }
+            }
+            Self::Report(value) => {
+                termprefix1(
+                    value.name(),
+                    format_args!(
+                        "{} - {} {}",
+                        value.from().format("%Y.%m.%d"),
+                        value.to().format("%Y.%m.%d"),
+                        format_args!("({})", RoundedFmt(value.delta(), value.rounded()))
+                            .fg_bright_black()
+                    ),
+                );
+                termh2("Days");
+                let days = value.days();
+                if days.is_empty() {
+                    termarrow("no tasks recorded!".fg_bright_black());
+                }
+                for total in days {
+                    term_total(total.name(), RoundedFmt::total(&total));
+                }
+                termh2("Weeks");
+                let weeks = value.weeks();
+                if weeks.is_empty() {
+                    termarrow("no tasks recorded!".fg_bright_black());
+                }
+                for total in weeks {
+                    term_total(total.name(), RoundedFmt::total(&total));
+                }
+                termh2("Tasks");
+                for total in value.tasks() {
+                    term_total(total.name(), RoundedFmt::total(&total));
+                }
+            }
+            Self::Balance(value) => {
+                termprefix1(
+                    "Balance",
+                    format_args!(
+                        "{} - {}",
+                        value.from().format("%Y.%m.%d"),
+                        value.to().format("%Y.%m.%d")
+                    ),
+                );
+                term_total("expected", DeltaFmt::new(value.expected()));
+                term_total("actual", DeltaFmt::new(value.actual()));
+                let color = if value.difference() < TimeDelta::zero() {
+                    Fg::Red
+                } else {
+                    Fg::Green
+                };
+                termarrow_fg(color, SignedDeltaFmt(value.difference()));
             }
             Self::End => println!(),
         }
     }
 }
 
+impl Default for DataBundle {
+    fn default() -> Self {
+        Self {
+            version: JSON_VERSION,
+            tasks: Vec::new(),
+            days: Vec::new(),
+            reports: Vec::new(),
+            balances: Vec::new(),
+            info: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
 impl BundleFmt for DataBundle {
     type Data = OutputData;
 
     fn push(&mut self, value: Self::Data) {
         match value {
-            OutputData::Error(value) => self.error.push(value),
-            OutputData::Task(value) => self.tasks.push(value),
-            OutputData::DayWithTask(value) => self.day_with_tasks.push(value),
+            OutputData::Info(value) => self.info.push(value),
+            OutputData::Error(value) => self.errors.push(value),
+            OutputData::Task(value) => self.tasks.push(TaskJson::from(&value)),
+            OutputData::DayWithTask(value) => self.days.push(DayJson::from(&value)),
+            OutputData::Report(value) => self.reports.push(ReportJson::from(&value)),
+            OutputData::Balance(value) => self.balances.push(BalanceJson::from(&value)),
             OutputData::End => {}
         }
     }
 
+    fn csv<Writer>(&self, mut writer: ::csv::Writer<Writer>) -> eyre::Result<()>
+    where
+        Writer: io::Write,
+    {
+        for row in csv::rows(self) {
+            writer.serialize(row)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
     fn clear(&mut self) {
         self.tasks.clear();
-        self.day_with_tasks.clear();
+        self.days.clear();
+        self.reports.clear();
+        self.balances.clear();
+        self.info.clear();
+        self.errors.clear();
     }
 }
 
-fn term_task_body(task: &Task<Day>) {
+fn term_task_body(task: &Task<Day>, rounded: Option<TimeDelta>) {
     let color = if task.is_active() { Fg::Green } else { Fg::Red };
     termarrow_fg(
         color,
         format_args!(
             "{} {}",
-            DeltaFmt::new(task.delta()),
+            RoundedFmt(task.delta(), rounded),
             format_args!(
                 "({} - {})",
                 TimeFmt::new(task.start()),
@@ -146,3 +320,313 @@ fn term_task_body(task: &Task<Day>) {
         ),
     );
 }
+
+fn term_total(name: &str, delta: impl Display) {
+    termarrow(format_args!(
+        "{} {}",
+        name,
+        format_args!("({})", delta).fg_bright_black()
+    ));
+}
+
+struct RoundedFmt(TimeDelta, Option<TimeDelta>);
+
+impl RoundedFmt {
+    fn total(total: &Total) -> Self {
+        Self(total.delta(), total.rounded())
+    }
+}
+
+impl Display for RoundedFmt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.1 {
+            Some(rounded) => write!(
+                f,
+                "{}, rounded {}",
+                DeltaFmt::new(self.0),
+                DeltaFmt::new(rounded)
+            ),
+            None => write!(f, "{}", DeltaFmt::new(self.0)),
+        }
+    }
+}
+
+struct SignedDeltaFmt(TimeDelta);
+
+impl Display for SignedDeltaFmt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0 < TimeDelta::zero() { "-" } else { "+" };
+        write!(f, "{}{}", sign, DeltaFmt::new(self.0.abs()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, str::FromStr};
+
+    use chrono::{Datelike, NaiveDate, NaiveTime, TimeDelta};
+
+    use crate::{
+        config::{Rate, WorkingHours},
+        database::open_in_memory_connection,
+        day::{Day, DayKind, DayRepository},
+        invoice::{write_invoice, Invoice, InvoiceFormat},
+        status::{write_status, Status, StatusFormat},
+        task::{Balance, DayWithTasks, Report, Rounding, TaskRepository},
+        time,
+    };
+
+    use super::{BundleFmt, DataBundle, OutputData};
+
+    struct Fixture {
+        days: DayRepository,
+        tasks: TaskRepository,
+    }
+
+    const TODAY: NaiveDate = NaiveDate::from_ymd_opt(2024, 3, 6).unwrap();
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn fixture() -> Fixture {
+        let connection = open_in_memory_connection().unwrap();
+        let days = DayRepository::new(connection.clone());
+        let tasks = TaskRepository::new(connection);
+        let acme = ["acme".to_owned()];
+
+        let monday = days.lookup(date(4)).unwrap();
+        tasks
+            .add(monday, "standup", &acme, at(9, 0), at(9, 15), false)
+            .unwrap();
+        tasks
+            .add(monday, "review", &acme, at(9, 15), at(11, 0), false)
+            .unwrap();
+
+        let wednesday = days.lookup(TODAY).unwrap();
+        tasks
+            .add(wednesday, "standup", &acme, at(9, 0), at(9, 15), false)
+            .unwrap();
+        let coffee = tasks
+            .add(wednesday, "coffee", &[], at(10, 0), at(10, 30), false)
+            .unwrap();
+        tasks.set_billable(coffee, false).unwrap();
+        let wednesday = days.lookup(TODAY).unwrap();
+        tasks
+            .start_at(wednesday, "deploy", &acme, at(11, 0))
+            .unwrap();
+
+        let friday = days.persist(days.lookup(date(8)).unwrap()).unwrap();
+        let friday = days.set_kind(friday, DayKind::Holiday).unwrap();
+        days.set_note(friday, Some("founders day")).unwrap();
+
+        Fixture { days, tasks }
+    }
+
+    impl Fixture {
+        fn day_with_tasks(&self, day: Day) -> DayWithTasks {
+            let note = self.days.note(&day).unwrap();
+            let expected = if day.kind().is_fulfilled() {
+                TimeDelta::zero()
+            } else {
+                WorkingHours::full_time().target(day.date().weekday())
+            };
+            self.tasks
+                .day_with_tasks(day)
+                .unwrap()
+                .with_note(note)
+                .with_expected(expected)
+        }
+
+        fn range(&self, from: NaiveDate, to: NaiveDate) -> Vec<DayWithTasks> {
+            self.days
+                .lookup_range(from, to)
+                .unwrap()
+                .into_iter()
+                .map(|day| self.day_with_tasks(day))
+                .collect()
+        }
+
+        fn report(&self, name: &str, (from, to): (NaiveDate, NaiveDate)) -> Report {
+            Report::new(name.to_owned(), from, to, self.range(from, to))
+        }
+    }
+
+    fn bundle(data: impl IntoIterator<Item = OutputData>) -> DataBundle {
+        let mut bundle = DataBundle::default();
+        for value in data {
+            bundle.push(value);
+        }
+        bundle
+    }
+
+    fn assert_snapshot(name: &str, actual: String) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/output/snapshots")
+            .join(name);
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::write(&path, &actual).unwrap();
+        }
+        let expected = fs::read_to_string(&path).unwrap();
+        assert_eq!(actual, expected, "the snapshot {} changed", name);
+    }
+
+    fn assert_output(name: &str, bundle: DataBundle) {
+        let json = serde_json::to_string_pretty(&bundle).unwrap() + "\n";
+        assert_snapshot(&format!("{}.json", name), json);
+        let mut csv = Vec::new();
+        bundle.csv(::csv::Writer::from_writer(&mut csv)).unwrap();
+        assert_snapshot(&format!("{}.csv", name), String::from_utf8(csv).unwrap());
+    }
+
+    #[test]
+    fn get() {
+        let fixture = fixture();
+        let today = fixture.days.lookup(TODAY).unwrap();
+        let task = fixture.tasks.current(today).unwrap();
+        assert_output("get", bundle([OutputData::Task(task)]));
+    }
+
+    #[test]
+    fn today() {
+        let fixture = fixture();
+        let today = fixture.days.lookup(TODAY).unwrap();
+        let day = fixture.day_with_tasks(today);
+        assert_output("today", bundle([OutputData::DayWithTask(day)]));
+    }
+
+    #[test]
+    fn day() {
+        let fixture = fixture();
+        let monday = fixture.days.lookup(date(4)).unwrap();
+        let day = fixture.day_with_tasks(monday);
+        assert_output("day", bundle([OutputData::DayWithTask(day)]));
+    }
+
+    #[test]
+    fn week() {
+        let fixture = fixture();
+        let days = fixture
+            .days
+            .week_till_date(TODAY)
+            .unwrap()
+            .into_iter()
+            .map(|day| OutputData::DayWithTask(fixture.day_with_tasks(day)));
+        assert_output("week", bundle(days));
+    }
+
+    #[test]
+    fn range() {
+        let fixture = fixture();
+        let days = fixture
+            .range(date(4), date(8))
+            .into_iter()
+            .map(OutputData::DayWithTask);
+        assert_output("range", bundle(days));
+    }
+
+    #[test]
+    fn month() {
+        let fixture = fixture();
+        let report = fixture.report("Month", time::month(TODAY, 0).unwrap());
+        assert_output("month", bundle([OutputData::Report(report)]));
+    }
+
+    #[test]
+    fn year() {
+        let fixture = fixture();
+        let report = fixture.report("Year", time::year(TODAY, 0).unwrap());
+        assert_output("year", bundle([OutputData::Report(report)]));
+    }
+
+    #[test]
+    fn balance() {
+        let fixture = fixture();
+        let days = fixture.range(date(4), date(8));
+        let balance = Balance::new(date(4), date(8), &days);
+        assert_output("balance", bundle([OutputData::Balance(balance)]));
+    }
+
+    #[test]
+    fn rounded() {
+        let fixture = fixture();
+        let rounding = Rounding::from_str("up:60:group").unwrap();
+        let days = fixture
+            .range(date(4), date(8))
+            .into_iter()
+            .map(|day| OutputData::DayWithTask(day.with_rounding(Some(rounding))));
+        assert_output("rounded", bundle(days));
+    }
+
+    #[test]
+    fn records() {
+        let fixture = fixture();
+        let today = fixture.days.lookup(TODAY).unwrap();
+        let task = fixture.tasks.current(today).unwrap();
+        assert_output(
+            "records",
+            bundle([
+                OutputData::Task(task),
+                OutputData::Info("done".to_owned()),
+                OutputData::Error("no task is started yet!".to_owned()),
+            ]),
+        );
+    }
+
+    #[test]
+    fn invoice() {
+        let fixture = fixture();
+        let tags = ["acme".to_owned()];
+        let rounding = Rounding::from_str("up:60:day").unwrap();
+        let rate = Rate {
+            rate: 95.0,
+            currency: "EUR".to_owned(),
+        };
+        let days = fixture
+            .range(date(4), date(8))
+            .into_iter()
+            .map(|day| {
+                day.filter_tags(&tags)
+                    .filter_billable()
+                    .with_rounding(Some(rounding))
+            })
+            .collect();
+        let invoice = Invoice::new("acme".to_owned(), date(4), date(8), &rate, days);
+        for (format, extension) in [
+            (InvoiceFormat::Json, "json"),
+            (InvoiceFormat::Markdown, "md"),
+            (InvoiceFormat::Html, "html"),
+        ] {
+            let mut output = Vec::new();
+            write_invoice(&mut output, format, &invoice).unwrap();
+            let name = format!("invoice.{}", extension);
+            assert_snapshot(&name, String::from_utf8(output).unwrap());
+        }
+    }
+
+    #[test]
+    fn status() {
+        let fixture = fixture();
+        let today = fixture.days.lookup(TODAY).unwrap();
+        let status = Status::new(&fixture.day_with_tasks(today));
+        for (format, name) in [
+            (StatusFormat::Waybar, "status.json"),
+            (StatusFormat::Text, "status.txt"),
+        ] {
+            let mut output = Vec::new();
+            write_status(
+                &mut output,
+                &format,
+                &status,
+                "{description} {elapsed}",
+                "idle",
+            )
+            .unwrap();
+            assert_snapshot(name, String::from_utf8(output).unwrap());
+        }
+    }
+}
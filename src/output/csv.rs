@@ -0,0 +1,115 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::day::DayKind;
+
+use super::{
+    json::{TaskJson, TotalJson},
+    DataBundle,
+};
+
+#[derive(Default, Serialize)]
+pub struct CsvRow<'a> {
+    record: &'static str,
+    date: Option<NaiveDate>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    id: Option<u64>,
+    name: Option<&'a str>,
+    kind: Option<DayKind>,
+    start: Option<&'a str>,
+    end: Option<&'a str>,
+    seconds: Option<i64>,
+    rounded_seconds: Option<i64>,
+    expected_seconds: Option<i64>,
+    tags: Option<String>,
+    billable: Option<bool>,
+    message: Option<&'a str>,
+}
+
+pub fn rows(bundle: &DataBundle) -> Vec<CsvRow<'_>> {
+    let mut rows: Vec<_> = bundle.tasks.iter().map(task).collect();
+    for day in &bundle.days {
+        rows.push(CsvRow {
+            record: "day",
+            date: Some(day.date),
+            kind: Some(day.kind),
+            seconds: Some(day.seconds),
+            rounded_seconds: day.rounded_seconds,
+            expected_seconds: Some(day.expected_seconds),
+            message: day.note.as_deref(),
+            ..CsvRow::default()
+        });
+        rows.extend(day.tasks.iter().map(task));
+        rows.extend(day.groups.iter().map(|group| CsvRow {
+            record: "group",
+            date: Some(day.date),
+            name: Some(&group.description),
+            seconds: Some(group.seconds),
+            rounded_seconds: group.rounded_seconds,
+            ..CsvRow::default()
+        }));
+        rows.extend(day.tags.iter().map(|tag| CsvRow {
+            date: Some(day.date),
+            ..total("tag", tag)
+        }));
+    }
+    for report in &bundle.reports {
+        rows.push(CsvRow {
+            record: "report",
+            from: Some(report.from),
+            to: Some(report.to),
+            name: Some(&report.name),
+            seconds: Some(report.seconds),
+            rounded_seconds: report.rounded_seconds,
+            ..CsvRow::default()
+        });
+        rows.extend(report.days.iter().map(|day| total("report-day", day)));
+        rows.extend(report.weeks.iter().map(|week| total("report-week", week)));
+        rows.extend(report.tasks.iter().map(|task| total("report-task", task)));
+    }
+    rows.extend(bundle.balances.iter().map(|balance| CsvRow {
+        record: "balance",
+        from: Some(balance.from),
+        to: Some(balance.to),
+        seconds: Some(balance.actual_seconds),
+        expected_seconds: Some(balance.expected_seconds),
+        ..CsvRow::default()
+    }));
+    rows.extend(bundle.info.iter().map(|info| message("info", info)));
+    rows.extend(bundle.errors.iter().map(|error| message("error", error)));
+    rows
+}
+
+fn task(task: &TaskJson) -> CsvRow<'_> {
+    CsvRow {
+        record: "task",
+        date: Some(task.date),
+        id: Some(task.id),
+        name: Some(&task.description),
+        start: Some(&task.start),
+        end: task.end.as_deref(),
+        seconds: Some(task.seconds),
+        tags: Some(task.tags.join(" ")),
+        billable: Some(task.billable),
+        ..CsvRow::default()
+    }
+}
+
+fn total<'a>(record: &'static str, total: &'a TotalJson) -> CsvRow<'a> {
+    CsvRow {
+        record,
+        name: Some(&total.name),
+        seconds: Some(total.seconds),
+        rounded_seconds: total.rounded_seconds,
+        ..CsvRow::default()
+    }
+}
+
+fn message<'a>(record: &'static str, message: &'a str) -> CsvRow<'a> {
+    CsvRow {
+        record,
+        message: Some(message),
+        ..CsvRow::default()
+    }
+}
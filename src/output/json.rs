@@ -0,0 +1,181 @@
+use chrono::{NaiveDate, NaiveTime, TimeDelta};
+use serde::Serialize;
+
+use crate::{
+    day::{Day, DayKind, END_OF_DAY},
+    task::{Balance, DayWithTasks, Report, Rounding, Task, TaskGroup, Total},
+};
+
+pub const JSON_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+pub struct TaskJson {
+    pub(super) id: u64,
+    pub(super) date: NaiveDate,
+    pub(super) start: String,
+    pub(super) end: Option<String>,
+    pub(super) seconds: i64,
+    pub(super) active: bool,
+    pub(super) description: String,
+    pub(super) tags: Vec<String>,
+    pub(super) billable: bool,
+}
+
+#[derive(Serialize)]
+pub struct DayJson {
+    pub(super) date: NaiveDate,
+    pub(super) kind: DayKind,
+    pub(super) note: Option<String>,
+    pub(super) seconds: i64,
+    pub(super) rounded_seconds: Option<i64>,
+    pub(super) expected_seconds: i64,
+    pub(super) tasks: Vec<TaskJson>,
+    pub(super) groups: Vec<GroupJson>,
+    pub(super) tags: Vec<TotalJson>,
+}
+
+#[derive(Serialize)]
+pub struct GroupJson {
+    pub(super) description: String,
+    pub(super) seconds: i64,
+    pub(super) rounded_seconds: Option<i64>,
+    pub(super) tasks: Vec<u64>,
+}
+
+#[derive(Serialize)]
+pub struct ReportJson {
+    pub(super) name: String,
+    pub(super) from: NaiveDate,
+    pub(super) to: NaiveDate,
+    pub(super) seconds: i64,
+    pub(super) rounded_seconds: Option<i64>,
+    pub(super) days: Vec<TotalJson>,
+    pub(super) weeks: Vec<TotalJson>,
+    pub(super) tasks: Vec<TotalJson>,
+}
+
+#[derive(Serialize)]
+pub struct BalanceJson {
+    pub(super) from: NaiveDate,
+    pub(super) to: NaiveDate,
+    pub(super) expected_seconds: i64,
+    pub(super) actual_seconds: i64,
+    pub(super) difference_seconds: i64,
+}
+
+#[derive(Serialize)]
+pub struct TotalJson {
+    pub(super) name: String,
+    pub(super) seconds: i64,
+    pub(super) rounded_seconds: Option<i64>,
+}
+
+impl From<&Task<Day>> for TaskJson {
+    fn from(task: &Task<Day>) -> Self {
+        Self {
+            id: task.id(),
+            date: task.day().date(),
+            start: time(task.start()),
+            end: task.end().map(time),
+            seconds: seconds(task.delta()),
+            active: task.is_active(),
+            description: task.description().to_owned(),
+            tags: task.tags().to_vec(),
+            billable: task.is_billable(),
+        }
+    }
+}
+
+impl From<&DayWithTasks> for DayJson {
+    fn from(day: &DayWithTasks) -> Self {
+        Self {
+            date: day.day().date(),
+            kind: day.day().kind(),
+            note: day.note().map(str::to_owned),
+            seconds: seconds(day.delta()),
+            rounded_seconds: day.rounded().map(seconds),
+            expected_seconds: seconds(day.expected()),
+            tasks: day.tasks().map(TaskJson::from).collect(),
+            groups: day
+                .task_groups()
+                .iter()
+                .map(|group| GroupJson::new(group, day.rounding()))
+                .collect(),
+            tags: day
+                .tag_groups()
+                .iter()
+                .map(|group| TotalJson::new(group.tag(), group.delta()))
+                .collect(),
+        }
+    }
+}
+
+impl GroupJson {
+    fn new(group: &TaskGroup, rounding: Option<&Rounding>) -> Self {
+        Self {
+            description: group.description().to_owned(),
+            seconds: seconds(group.delta()),
+            rounded_seconds: rounding
+                .and_then(|rounding| group.rounded(rounding))
+                .map(seconds),
+            tasks: group.tasks().map(|task| task.id()).collect(),
+        }
+    }
+}
+
+impl From<&Report> for ReportJson {
+    fn from(report: &Report) -> Self {
+        Self {
+            name: report.name().to_owned(),
+            from: report.from(),
+            to: report.to(),
+            seconds: seconds(report.delta()),
+            rounded_seconds: report.rounded().map(seconds),
+            days: report.days().iter().map(TotalJson::from).collect(),
+            weeks: report.weeks().iter().map(TotalJson::from).collect(),
+            tasks: report.tasks().iter().map(TotalJson::from).collect(),
+        }
+    }
+}
+
+impl From<&Balance> for BalanceJson {
+    fn from(balance: &Balance) -> Self {
+        Self {
+            from: balance.from(),
+            to: balance.to(),
+            expected_seconds: seconds(balance.expected()),
+            actual_seconds: seconds(balance.actual()),
+            difference_seconds: seconds(balance.difference()),
+        }
+    }
+}
+
+impl From<&Total> for TotalJson {
+    fn from(total: &Total) -> Self {
+        Self {
+            rounded_seconds: total.rounded().map(seconds),
+            ..Self::new(total.name(), total.delta())
+        }
+    }
+}
+
+impl TotalJson {
+    fn new(name: &str, delta: TimeDelta) -> Self {
+        Self {
+            name: name.to_owned(),
+            seconds: seconds(delta),
+            rounded_seconds: None,
+        }
+    }
+}
+
+fn time(time: NaiveTime) -> String {
+    if time == END_OF_DAY {
+        return "24:00:00".to_owned();
+    }
+    time.format("%H:%M:%S").to_string()
+}
+
+fn seconds(delta: TimeDelta) -> i64 {
+    delta.num_seconds()
+}
@@ -0,0 +1,124 @@
+use std::{
+    fmt::Display,
+    io::{stdout, Write},
+    thread::sleep,
+    time::Duration,
+};
+
+use chrono::{Local, TimeDelta, Timelike};
+use eyre::Context;
+use serde::Serialize;
+
+use crate::{
+    day::{Day, DayRepository},
+    task::{DayWithTasks, Task, TaskRepository},
+};
+
+pub enum StatusFormat {
+    Text,
+    Waybar,
+}
+
+pub struct Status {
+    task: Option<Task<Day>>,
+    today: TimeDelta,
+}
+
+#[derive(Serialize)]
+struct WaybarJson {
+    text: String,
+    alt: &'static str,
+    tooltip: String,
+    class: &'static str,
+}
+
+impl Status {
+    pub fn new(day: &DayWithTasks) -> Self {
+        Self {
+            task: day.tasks().find(|task| task.is_active()).cloned(),
+            today: day.delta(),
+        }
+    }
+
+    pub fn render(&self, template: &str) -> String {
+        let mut text = template.replace("{today}", &ElapsedFmt(self.today).to_string());
+        let Some(task) = &self.task else {
+            return text;
+        };
+        for (placeholder, value) in [
+            ("{description}", task.description().to_owned()),
+            ("{tags}", task.tags().join(",")),
+            ("{start}", task.start().format("%H:%M").to_string()),
+            ("{elapsed}", ElapsedFmt(task.delta()).to_string()),
+        ] {
+            text = text.replace(placeholder, &value);
+        }
+        text
+    }
+
+    fn class(&self) -> &'static str {
+        match self.task {
+            Some(_) => "active",
+            None => "idle",
+        }
+    }
+
+    fn tooltip(&self) -> String {
+        match &self.task {
+            Some(_) => self.render("{description} since {start}, {today} today"),
+            None => self.render("{today} today"),
+        }
+    }
+}
+
+pub fn write_status(
+    mut writer: impl Write,
+    format: &StatusFormat,
+    status: &Status,
+    template: &str,
+    idle: &str,
+) -> eyre::Result<()> {
+    let text = match status.task {
+        Some(_) => status.render(template),
+        None => status.render(idle),
+    };
+    match format {
+        StatusFormat::Text => writeln!(writer, "{}", text)?,
+        StatusFormat::Waybar => {
+            let json = WaybarJson {
+                text,
+                alt: status.class(),
+                tooltip: status.tooltip(),
+                class: status.class(),
+            };
+            serde_json::to_writer(&mut writer, &json)
+                .wrap_err("could not write the waybar status")?;
+            writeln!(writer)?;
+        }
+    }
+    writer.flush().wrap_err("could not write the status")
+}
+
+pub fn watch(
+    days: &DayRepository,
+    tasks: &TaskRepository,
+    format: &StatusFormat,
+    template: &str,
+    idle: &str,
+) -> eyre::Result<()> {
+    loop {
+        let today = Local::now().date_naive();
+        let day = tasks.day_with_tasks(days.lookup(today)?)?;
+        write_status(stdout().lock(), format, &Status::new(&day), template, idle)?;
+        let seconds = 60 - u64::from(Local::now().second());
+        sleep(Duration::from_secs(seconds));
+    }
+}
+
+struct ElapsedFmt(TimeDelta);
+
+impl Display for ElapsedFmt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{:02}", self.0.num_hours(), self.0.num_minutes() % 60)
+    }
+}
@@ -1,26 +1,40 @@
 use std::rc::Rc;
 
-use chrono::{Local, NaiveTime, TimeDelta};
+use chrono::{Local, NaiveDate, NaiveTime, TimeDelta};
 use eyre::{eyre, Context};
 use rusqlite::{Connection, Params, Row};
 
-pub use dto::{DayWithTasks, Task, TaskGroup};
+pub use dto::{Balance, DayWithTasks, Report, Rounding, RoundingScope, Task, TaskGroup, Total};
+pub use journal::Operation;
 
-use crate::day::{Day, DayRef};
+use crate::{
+    database::Savepoint,
+    day::{Day, DayRef, DayRepository, END_OF_DAY},
+};
 
-use self::dto::MutTask;
+use self::{dto::MutTask, fuzzy::fuzzy_match, journal::Journal};
 
 mod dto;
+mod fuzzy;
+mod journal;
+
+const TAG_SEPARATOR: char = '\u{1f}';
+const FUZZY_CANDIDATES: usize = 1000;
 
 pub struct TaskRepository {
     connection: Rc<Connection>,
+    days: DayRepository,
+    journal: Journal,
 }
 
 impl TaskRepository {
     pub fn day_with_tasks(&self, day: Day) -> eyre::Result<DayWithTasks> {
         let mut tasks = self
             .query(
-                "SELECT id, day_id, start, end, description FROM tasks WHERE day_id=?1",
+                "SELECT id, day_id, start, end, description, tags, billable
+                 FROM tasks_with_tags
+                 WHERE day_id=?1
+                 ORDER BY start",
                 (day.id(),),
             )
             .with_context(|| format!("cannot query tasks for day: {:?}", day))?;
@@ -31,23 +45,133 @@ impl TaskRepository {
         Ok(DayWithTasks::new(day, tasks))
     }
 
-    pub fn start(&self, day: Day, description: &str) -> eyre::Result<Task<Day>> {
-        let desciption = description.trim();
+    pub fn start(&self, day: Day, description: &str, tags: &[String]) -> eyre::Result<Task<Day>> {
         if self.current(day).is_ok() {
             self.stop(day)
                 .with_context(|| "could not end the current task before starting a new one.")?;
         };
         let now = Local::now().time();
-        self.connection
-            .execute(
-                "INSERT INTO tasks (day_id, start, end, description)
-                 VALUES (?1, ?2, null, ?3)",
-                (day.id(), now, description),
-            )
-            .wrap_err("could not start a new task")
-            .with_context(|| description.to_owned())?;
-        self.current(day)
-            .wrap_err("could not get newly created task")
+        let task = self
+            .insert(day, now, None, description)
+            .wrap_err("could not start a new task")?;
+        self.set_tags(task, tags.to_vec())
+    }
+
+    pub fn start_at(
+        &self,
+        day: Day,
+        description: &str,
+        tags: &[String],
+        start: NaiveTime,
+    ) -> eyre::Result<Task<Day>> {
+        if let Some(task) = self.running()? {
+            let error = Err(eyre!("another task is already running"));
+            return error.with_context(|| format!("{}", task));
+        }
+        if let Some(task) = self.overlapping(day, start, day.time())?.first() {
+            let error = Err(eyre!("the task overlaps with an existing task"));
+            return error.with_context(|| format!("{}", task));
+        }
+        let task = self
+            .insert(day, start, None, description)
+            .wrap_err("could not start a new task")?;
+        self.set_tags(task, tags.to_vec())
+    }
+
+    pub fn add(
+        &self,
+        day: Day,
+        description: &str,
+        tags: &[String],
+        start: NaiveTime,
+        end: NaiveTime,
+        trim: bool,
+    ) -> eyre::Result<Task<Day>> {
+        if start >= end {
+            return Err(eyre!(
+                "cannot add a task that ends before it starts: {} >= {}",
+                start,
+                end
+            ));
+        }
+        for task in self.overlapping(day, start, end)? {
+            if !trim {
+                let error = Err(eyre!("the task overlaps with an existing task"));
+                return error.with_context(|| format!("{}", task));
+            }
+            self.trim(task, start, end)?;
+        }
+        let task = self
+            .insert(day, start, Some(end), description)
+            .wrap_err("could not add a new task")?;
+        self.set_tags(task, tags.to_vec())
+    }
+
+    pub fn overlapping(
+        &self,
+        day: Day,
+        start: NaiveTime,
+        end: NaiveTime,
+    ) -> eyre::Result<Vec<Task<Day>>> {
+        let tasks = self
+            .day_with_tasks(day)?
+            .tasks()
+            .filter(|task| task.start() < end && task.end().unwrap_or(END_OF_DAY) > start)
+            .cloned()
+            .collect();
+        Ok(tasks)
+    }
+
+    fn trim(&self, mut task: Task<Day>, start: NaiveTime, end: NaiveTime) -> eyre::Result<()> {
+        if task.is_active() {
+            let error = Err(eyre!("cannot trim the currently running task"));
+            return error.with_context(|| format!("{}", task));
+        }
+        let starts_before = task.start() < start;
+        let ends_after = task.end_or_day_time() > end;
+        match (starts_before, ends_after) {
+            (true, false) => MutTask::set_end(&mut task, start),
+            (false, true) => MutTask::set_start(&mut task, end),
+            _ => {
+                let error = Err(eyre!(
+                    "cannot trim a task that would need to be removed or split"
+                ));
+                return error.with_context(|| format!("{}", task));
+            }
+        }
+        self.save(&task)
+    }
+
+    pub fn carry_over(&self, date: NaiveDate) -> eyre::Result<Vec<Task<Day>>> {
+        let running = self.query(
+            "SELECT id, day_id, start, end, description, tags, billable
+             FROM tasks_with_tags
+             WHERE end IS null AND day_id IN (SELECT id FROM days WHERE date < ?1)",
+            (date,),
+        )?;
+        if running.is_empty() {
+            return Ok(Vec::new());
+        }
+        let today = self.days.lookup(date)?;
+        let mut carried = Vec::new();
+        for task in running {
+            let day = self.days.day(task.day())?;
+            let description = task.description().to_owned();
+            let tags = task.tags().to_vec();
+            let billable = task.is_billable();
+            let task = self.with_day(task, day)?;
+            self.set_end(task, END_OF_DAY)?;
+            let mut date = day.date();
+            while let Some(next) = date.succ_opt().filter(|next| *next < today.date()) {
+                let day = self.days.lookup(next)?;
+                let task = self.insert(day, NaiveTime::MIN, Some(END_OF_DAY), &description)?;
+                self.set_tags(task.with_billable(billable), tags.clone())?;
+                date = next;
+            }
+            let task = self.insert(today, NaiveTime::MIN, None, &description)?;
+            carried.push(self.set_tags(task.with_billable(billable), tags)?);
+        }
+        Ok(carried)
     }
 
     pub fn stop(&self, day: Day) -> eyre::Result<Task<Day>> {
@@ -61,7 +185,7 @@ impl TaskRepository {
         }
         let now = Local::now().time();
         MutTask::set_end(&mut current, now);
-        self.save(&current);
+        self.save(&current)?;
         Ok(current)
     }
 
@@ -83,6 +207,32 @@ impl TaskRepository {
         Ok(task)
     }
 
+    pub fn set_billable<DayRefImpl>(
+        &self,
+        mut task: Task<DayRefImpl>,
+        billable: bool,
+    ) -> eyre::Result<Task<DayRefImpl>>
+    where
+        DayRefImpl: DayRef,
+    {
+        MutTask::set_billable(&mut task, billable);
+        self.save(&task)?;
+        Ok(task)
+    }
+
+    pub fn set_tags<DayRefImpl>(
+        &self,
+        mut task: Task<DayRefImpl>,
+        tags: Vec<String>,
+    ) -> eyre::Result<Task<DayRefImpl>>
+    where
+        DayRefImpl: DayRef,
+    {
+        MutTask::set_tags(&mut task, tags);
+        self.save(&task)?;
+        Ok(task)
+    }
+
     pub fn shift_start(&self, task: Task<Day>, delta: TimeDelta) -> eyre::Result<Task<Day>> {
         let time = task.start() + delta;
         self.set_start(task, time)
@@ -92,10 +242,8 @@ impl TaskRepository {
         if time == task.start() {
             return Ok(task);
         }
-        if time > task.start() {
-            if time >= task.end_or_day_time() {
-                return Err(eyre!("cannot set start past the end time"));
-            }
+        if time > task.start() && time >= task.end_or_day_time() {
+            return Err(eyre!("cannot set start past the end time"));
         }
         if let Some(prev) = self.prev(&task)? {
             let prev_end = prev.end_or_day_time();
@@ -116,26 +264,131 @@ impl TaskRepository {
                 task.start()
             ));
         }
+        if let Some(mut next) = self.next(&task)? {
+            let next_start = next.start();
+            if Some(next_start) == task.end() || next_start < time {
+                if time >= next.end_or_day_time() {
+                    return Err(eyre!("cannot set end past the end of the next task"));
+                }
+                MutTask::set_start(&mut next, time);
+                self.save(&next)?;
+            }
+        }
         MutTask::set_end(&mut task, time);
         self.save(&task)?;
         Ok(task)
     }
 
+    pub fn set_range(
+        &self,
+        task: Task<Day>,
+        start: Option<NaiveTime>,
+        end: Option<NaiveTime>,
+    ) -> eyre::Result<Task<Day>> {
+        match (start, end) {
+            (Some(start), Some(end)) if start >= end => Err(eyre!(
+                "cannot set start time after end time: {} >= {}",
+                start,
+                end
+            )),
+            (Some(start), Some(end)) if start >= task.end_or_day_time() => {
+                let task = self.set_end(task, end)?;
+                self.set_start(task, start)
+            }
+            (Some(start), end) => {
+                let task = self.set_start(task, start)?;
+                match end {
+                    Some(end) => self.set_end(task, end),
+                    None => Ok(task),
+                }
+            }
+            (None, Some(end)) => self.set_end(task, end),
+            (None, None) => Ok(task),
+        }
+    }
+
     pub fn current(&self, day: Day) -> eyre::Result<Task<Day>> {
         let task = self.get(
-            "SELECT id, day_id, start, end, description
-             FROM tasks
+            "SELECT id, day_id, start, end, description, tags, billable
+             FROM tasks_with_tags
              WHERE day_id=?1 AND end IS null",
             (day.id(),),
         )?;
         Ok(MutTask::with_day(task, day))
     }
 
+    pub fn recent_descriptions(&self, count: usize) -> eyre::Result<Vec<String>> {
+        let query = "SELECT description FROM tasks
+             GROUP BY description
+             ORDER BY MAX(id) DESC
+             LIMIT ?1";
+        self.connection
+            .prepare(query)?
+            .query_map((count,), |row| row.get(0))
+            .wrap_err("could not execute sql statement")
+            .with_context(|| query.to_owned())?
+            .collect::<Result<_, _>>()
+            .wrap_err("cannot convert descriptions from sql statement")
+            .with_context(|| query.to_owned())
+    }
+
+    pub fn recent_tags(&self, count: usize) -> eyre::Result<Vec<String>> {
+        let query = "SELECT tags.name FROM tags JOIN task_tags ON tags.id = task_tags.tag_id
+             GROUP BY tags.name
+             ORDER BY MAX(task_tags.task_id) DESC
+             LIMIT ?1";
+        self.connection
+            .prepare(query)?
+            .query_map((count,), |row| row.get(0))
+            .wrap_err("could not execute sql statement")
+            .with_context(|| query.to_owned())?
+            .collect::<Result<_, _>>()
+            .wrap_err("cannot convert tags from sql statement")
+            .with_context(|| query.to_owned())
+    }
+
+    pub fn recent_tasks(&self, count: usize) -> eyre::Result<Vec<Task<u64>>> {
+        self.query(
+            "SELECT id, day_id, start, end, description, tags, billable
+             FROM tasks_with_tags
+             WHERE id IN (SELECT MAX(id) FROM tasks WHERE end IS NOT null GROUP BY description)
+             ORDER BY id DESC
+             LIMIT ?1",
+            (count,),
+        )
+    }
+
+    pub fn match_description(&self, query: &str) -> eyre::Result<String> {
+        let descriptions = self.recent_descriptions(FUZZY_CANDIDATES)?;
+        fuzzy_match(query, &descriptions).map(str::to_owned)
+    }
+
+    pub fn last_stopped(&self) -> eyre::Result<Option<Task<u64>>> {
+        self.get_opt(
+            "SELECT tasks_with_tags.id, day_id, start, end, description, tags, billable
+             FROM tasks_with_tags JOIN days ON days.id = tasks_with_tags.day_id
+             WHERE end IS NOT null
+             ORDER BY days.date DESC, end DESC
+             LIMIT 1",
+            (),
+        )
+    }
+
+    pub fn running(&self) -> eyre::Result<Option<Task<u64>>> {
+        self.get_opt(
+            "SELECT id, day_id, start, end, description, tags, billable
+             FROM tasks_with_tags
+             WHERE end IS null
+             LIMIT 1",
+            (),
+        )
+    }
+
     pub fn prev(&self, task: &Task<Day>) -> eyre::Result<Option<Task<Day>>> {
         let prev = self
             .get_opt(
-                "SELECT id, day_id, start, end, description
-                 FROM tasks
+                "SELECT id, day_id, start, end, description, tags, billable
+                 FROM tasks_with_tags
                  WHERE day_id=?1 AND end <= ?2
                  ORDER BY end DESC
                  LIMIT 1",
@@ -145,10 +398,92 @@ impl TaskRepository {
         Ok(prev)
     }
 
+    pub fn next(&self, task: &Task<Day>) -> eyre::Result<Option<Task<Day>>> {
+        let next = self
+            .get_opt(
+                "SELECT id, day_id, start, end, description, tags, billable
+                 FROM tasks_with_tags
+                 WHERE day_id=?1 AND start >= ?2 AND id != ?3
+                 ORDER BY start ASC
+                 LIMIT 1",
+                (task.day_id(), task.start(), task.id()),
+            )?
+            .map(|next| MutTask::with_day(next, task.day()));
+        Ok(next)
+    }
+
+    pub fn delete(&self, id: u64) -> eyre::Result<Task<u64>> {
+        let task = self.task(id)?;
+        self.connection
+            .execute("DELETE FROM task_tags WHERE task_id=?1", (id,))?;
+        self.connection
+            .execute("DELETE FROM tasks WHERE id=?1", (id,))
+            .wrap_err("could not delete the task")
+            .with_context(|| format!("{}", task))?;
+        self.journal.record(id, Some(&task), None::<&Task<u64>>)?;
+        Ok(task)
+    }
+
+    pub fn begin(&self, operation: &str) {
+        self.journal.begin(operation)
+    }
+
+    pub fn undo(&self, count: usize) -> eyre::Result<Vec<Operation>> {
+        let transaction = self.transaction()?;
+        let operations = self.journal.operations(count)?;
+        for operation in operations.iter() {
+            for entry in self.journal.entries(operation)? {
+                self.restore(entry.task_id, entry.before)
+                    .wrap_err("could not undo the operation")
+                    .with_context(|| operation.name().to_owned())?;
+            }
+            self.journal.remove(operation)?;
+        }
+        transaction.commit()?;
+        Ok(operations)
+    }
+
+    pub fn transaction(&self) -> eyre::Result<Savepoint<'_>> {
+        Savepoint::new(&self.connection)
+    }
+
+    pub fn all(&self) -> eyre::Result<Vec<Task<u64>>> {
+        self.query(
+            "SELECT id, day_id, start, end, description, tags, billable
+             FROM tasks_with_tags
+             ORDER BY day_id, start",
+            (),
+        )
+    }
+
+    pub fn restore_task(&self, task: Task<u64>) -> eyre::Result<()> {
+        self.restore(task.id(), Some(task))
+    }
+
+    pub fn clear(&self) -> eyre::Result<()> {
+        self.connection
+            .execute_batch(
+                "DELETE FROM task_tags;
+                 DELETE FROM tasks;
+                 DELETE FROM tags;
+                 DELETE FROM journal;
+                 DELETE FROM operations;",
+            )
+            .wrap_err("could not delete the tasks")
+    }
+
+    pub fn with_day(&self, task: Task<u64>, day: Day) -> eyre::Result<Task<Day>> {
+        if task.day() != day.id() {
+            let error = Err(eyre!("task does not belong to the day"));
+            return error.with_context(|| format!("{:?} {:?}", task, day));
+        }
+        Ok(MutTask::with_day(task, day))
+    }
+
     pub fn task(&self, id: u64) -> eyre::Result<Task<u64>> {
         self.get(
-            "SELECT id, day_id, start, end, description
-             FROM tasks
+            "SELECT id, day_id, start, end, description, tags, billable
+             FROM tasks_with_tags
              WHERE id=?1",
             (id,),
         )
@@ -156,23 +491,19 @@ impl TaskRepository {
 }
 
 impl TaskRepository {
-    pub fn new(connection: Rc<Connection>) -> eyre::Result<Self> {
-        let _ = connection.execute(
-            "CREATE TABLE IF NOT EXISTS tasks (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                day_id INTEGER NOT NULL,
-                start DATE NOT NULL,
-                end DATE,
-                description TEXT NOT NULL
-            )",
-            (),
-        )?;
-        Ok(Self { connection })
+    pub fn new(connection: Rc<Connection>) -> Self {
+        let days = DayRepository::new(connection.clone());
+        let journal = Journal::new(connection.clone());
+        Self {
+            connection,
+            days,
+            journal,
+        }
     }
 
     fn get_opt(&self, query: &str, params: impl Params) -> eyre::Result<Option<Task<u64>>> {
         let tasks = self.query(query, params)?;
-        if tasks.len() == 0 {
+        if tasks.is_empty() {
             return Ok(None);
         }
         if tasks.len() == 1 {
@@ -195,23 +526,85 @@ impl TaskRepository {
             .query_map(params, task_from_row)
             .wrap_err("could not execute sql statement")
             .with_context(|| query.to_owned())?
-            .into_iter()
             .collect::<Result<_, _>>()
             .wrap_err("cannot convert tasks from sql statement")
             .with_context(|| query.to_owned())
     }
 
+    fn insert(
+        &self,
+        day: Day,
+        start: NaiveTime,
+        end: Option<NaiveTime>,
+        description: &str,
+    ) -> eyre::Result<Task<Day>> {
+        let day = self.days.persist(day)?;
+        self.connection
+            .execute(
+                "INSERT INTO tasks (day_id, start, end, description)
+                 VALUES (?1, ?2, ?3, ?4)",
+                (day.id(), start, end, description.trim()),
+            )
+            .with_context(|| description.to_owned())?;
+        let task = self.task(self.connection.last_insert_rowid() as u64)?;
+        self.journal
+            .record(task.id(), None::<&Task<u64>>, Some(&task))?;
+        Ok(MutTask::with_day(task, day))
+    }
+
     fn save(&self, task: &Task<impl DayRef>) -> eyre::Result<()> {
+        let before = self.task(task.id())?;
         self.connection.execute(
-            "UPDATE tasks SET day_id=?1, start=?2, end=?3, description=?4 WHERE id=?5",
+            "UPDATE tasks SET day_id=?1, start=?2, end=?3, description=?4, billable=?5
+             WHERE id=?6",
             (
                 task.day_id(),
                 task.start(),
                 task.end(),
                 task.description(),
+                task.is_billable(),
                 task.id(),
             ),
         )?;
+        self.save_tags(task)?;
+        self.journal.record(task.id(), Some(&before), Some(task))
+    }
+
+    fn restore(&self, id: u64, task: Option<Task<u64>>) -> eyre::Result<()> {
+        let Some(task) = task else {
+            self.connection
+                .execute("DELETE FROM task_tags WHERE task_id=?1", (id,))?;
+            self.connection
+                .execute("DELETE FROM tasks WHERE id=?1", (id,))?;
+            return Ok(());
+        };
+        self.connection.execute(
+            "INSERT OR REPLACE INTO tasks (id, day_id, start, end, description, billable)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            (
+                task.id(),
+                task.day_id(),
+                task.start(),
+                task.end(),
+                task.description(),
+                task.is_billable(),
+            ),
+        )?;
+        self.save_tags(&task)
+    }
+
+    fn save_tags(&self, task: &Task<impl DayRef>) -> eyre::Result<()> {
+        self.connection
+            .execute("DELETE FROM task_tags WHERE task_id=?1", (task.id(),))?;
+        for tag in task.tags() {
+            self.connection
+                .execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", (tag,))?;
+            self.connection.execute(
+                "INSERT INTO task_tags (task_id, tag_id)
+                 SELECT ?1, id FROM tags WHERE name=?2",
+                (task.id(), tag),
+            )?;
+        }
         Ok(())
     }
 }
@@ -223,5 +616,43 @@ pub fn task_from_row(row: &Row) -> rusqlite::Result<Task<u64>> {
     let end = row.get("end")?;
     let description: String = row.get("description")?;
     let description = description.trim();
-    Ok(Task::new(id, day, start, end, description.to_owned()))
+    let tags: Option<String> = row.get("tags")?;
+    let tags = tags
+        .map(|tags| tags.split(TAG_SEPARATOR).map(str::to_owned).collect())
+        .unwrap_or_default();
+    let billable = row.get("billable")?;
+    Ok(Task::new(id, day, start, end, description.to_owned(), tags).with_billable(billable))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveTime};
+
+    use crate::{database::open_in_memory_connection, day::DayRepository};
+
+    use super::TaskRepository;
+
+    #[test]
+    fn undo_restores_a_task_split_at_midnight() {
+        let connection = open_in_memory_connection().unwrap();
+        let days = DayRepository::new(connection.clone());
+        let tasks = TaskRepository::new(connection);
+        let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let wednesday = NaiveDate::from_ymd_opt(2024, 3, 6).unwrap();
+        let start = NaiveTime::from_hms_opt(22, 0, 0).unwrap();
+        let day = days.lookup(monday).unwrap();
+        let running = tasks.start_at(day, "deploy", &[], start).unwrap();
+
+        tasks.begin("stop");
+        let transaction = tasks.transaction().unwrap();
+        tasks.carry_over(wednesday).unwrap();
+        transaction.commit().unwrap();
+        assert_eq!(tasks.all().unwrap().len(), 3);
+
+        tasks.undo(1).unwrap();
+        let all = tasks.all().unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].id(), running.id());
+        assert_eq!(all[0].end(), None);
+    }
 }
@@ -1,9 +1,17 @@
 pub use {
+    balance::Balance,
     day_with_tasks::DayWithTasks,
+    report::{Report, Total},
+    rounding::{Rounding, RoundingMode, RoundingScope},
+    tag_group::TagGroup,
     task_group::TaskGroup,
     value::{MutTask, Task},
 };
 
+mod balance;
 mod day_with_tasks;
+mod report;
+mod rounding;
+mod tag_group;
 mod task_group;
 mod value;
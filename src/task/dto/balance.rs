@@ -0,0 +1,41 @@
+use chrono::{NaiveDate, TimeDelta};
+
+use super::DayWithTasks;
+
+pub struct Balance {
+    from: NaiveDate,
+    to: NaiveDate,
+    expected: TimeDelta,
+    actual: TimeDelta,
+}
+
+impl Balance {
+    pub fn new(from: NaiveDate, to: NaiveDate, days: &[DayWithTasks]) -> Self {
+        Self {
+            from,
+            to,
+            expected: days.iter().map(|day| day.expected()).sum(),
+            actual: days.iter().map(|day| day.delta()).sum(),
+        }
+    }
+
+    pub fn from(&self) -> NaiveDate {
+        self.from
+    }
+
+    pub fn to(&self) -> NaiveDate {
+        self.to
+    }
+
+    pub fn expected(&self) -> TimeDelta {
+        self.expected
+    }
+
+    pub fn actual(&self) -> TimeDelta {
+        self.actual
+    }
+
+    pub fn difference(&self) -> TimeDelta {
+        self.actual - self.expected
+    }
+}
@@ -4,17 +4,63 @@ use serde::Serialize;
 
 use crate::day::Day;
 
-use super::{task_group::TaskGroup, Task};
+use super::{tag_group::TagGroup, task_group::TaskGroup, Rounding, RoundingScope, Task};
 
 #[derive(Serialize)]
 pub struct DayWithTasks {
     day: Day,
     tasks: Vec<Task<Day>>,
+    #[serde(skip)]
+    expected: TimeDelta,
+    #[serde(skip)]
+    note: Option<String>,
+    #[serde(skip)]
+    rounding: Option<Rounding>,
 }
 
 impl DayWithTasks {
     pub fn new(day: Day, tasks: Vec<Task<Day>>) -> Self {
-        Self { day, tasks }
+        Self {
+            day,
+            tasks,
+            expected: TimeDelta::zero(),
+            note: None,
+            rounding: None,
+        }
+    }
+
+    pub fn with_expected(mut self, expected: TimeDelta) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    pub fn with_note(mut self, note: Option<String>) -> Self {
+        self.note = note;
+        self
+    }
+
+    pub fn with_rounding(mut self, rounding: Option<Rounding>) -> Self {
+        self.rounding = rounding.filter(|rounding| !rounding.is_exact());
+        self
+    }
+
+    pub fn filter_tags(mut self, tags: &[String]) -> Self {
+        if tags.is_empty() {
+            return self;
+        }
+        self.tasks
+            .retain(|task| tags.iter().any(|tag| task.has_tag(tag)));
+        self
+    }
+
+    pub fn filter_billable(mut self) -> Self {
+        self.tasks.retain(|task| task.is_billable());
+        self
+    }
+
+    pub fn filter_closed(mut self) -> Self {
+        self.tasks.retain(|task| !task.is_active());
+        self
     }
 
     pub fn is_empty(&self) -> bool {
@@ -25,6 +71,40 @@ impl DayWithTasks {
         self.tasks.iter().map(|task| task.delta()).sum()
     }
 
+    pub fn rounding(&self) -> Option<&Rounding> {
+        self.rounding.as_ref()
+    }
+
+    pub fn rounded(&self) -> Option<TimeDelta> {
+        let rounding = self.rounding.as_ref()?;
+        let rounded = match rounding.per() {
+            RoundingScope::Task => self
+                .tasks
+                .iter()
+                .map(|task| rounding.round(task.delta()))
+                .sum(),
+            RoundingScope::Group => self
+                .task_groups()
+                .iter()
+                .filter_map(|group| group.rounded(rounding))
+                .sum(),
+            RoundingScope::Day => rounding.round(self.delta()),
+        };
+        Some(rounded)
+    }
+
+    pub fn expected(&self) -> TimeDelta {
+        self.expected
+    }
+
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    pub fn overtime(&self) -> TimeDelta {
+        self.delta() - self.expected
+    }
+
     pub fn day(&self) -> &Day {
         &self.day
     }
@@ -47,4 +127,23 @@ impl DayWithTasks {
 
         groups
     }
+
+    pub fn tag_groups(&self) -> Vec<TagGroup> {
+        let mut groups: Vec<_> = self
+            .tasks
+            .iter()
+            .flat_map(|task| {
+                task.tags()
+                    .iter()
+                    .map(move |tag| (tag.clone(), task.clone()))
+            })
+            .into_group_map()
+            .into_iter()
+            .map(|(key, value)| TagGroup::new(key, value))
+            .collect();
+
+        groups.sort_by(|left, right| left.tag().cmp(right.tag()));
+
+        groups
+    }
 }
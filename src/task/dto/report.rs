@@ -0,0 +1,130 @@
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use itertools::Itertools;
+
+use super::DayWithTasks;
+
+pub struct Report {
+    name: String,
+    from: NaiveDate,
+    to: NaiveDate,
+    days: Vec<DayWithTasks>,
+}
+
+pub struct Total {
+    name: String,
+    delta: TimeDelta,
+    rounded: Option<TimeDelta>,
+}
+
+impl Report {
+    pub fn new(name: String, from: NaiveDate, to: NaiveDate, days: Vec<DayWithTasks>) -> Self {
+        Self {
+            name,
+            from,
+            to,
+            days,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn from(&self) -> NaiveDate {
+        self.from
+    }
+
+    pub fn to(&self) -> NaiveDate {
+        self.to
+    }
+
+    pub fn delta(&self) -> TimeDelta {
+        self.days.iter().map(|day| day.delta()).sum()
+    }
+
+    pub fn rounded(&self) -> Option<TimeDelta> {
+        self.days.iter().map(|day| day.rounded()).sum()
+    }
+
+    pub fn days(&self) -> Vec<Total> {
+        self.days
+            .iter()
+            .filter(|day| !day.is_empty())
+            .map(|day| {
+                Total::new(day.day().date().to_string(), day.delta()).with_rounded(day.rounded())
+            })
+            .collect()
+    }
+
+    pub fn weeks(&self) -> Vec<Total> {
+        self.days
+            .iter()
+            .filter(|day| !day.is_empty())
+            .group_by(|day| day.day().date().iso_week())
+            .into_iter()
+            .map(|(week, days)| {
+                let name = format!("{}-W{:02}", week.year(), week.week());
+                let days: Vec<_> = days.collect();
+                let delta = days.iter().map(|day| day.delta()).sum();
+                let rounded = days.iter().map(|day| day.rounded()).sum();
+                Total::new(name, delta).with_rounded(rounded)
+            })
+            .collect()
+    }
+
+    pub fn tasks(&self) -> Vec<Total> {
+        let mut totals: Vec<_> = self
+            .days
+            .iter()
+            .flat_map(|day| {
+                day.task_groups().into_iter().map(|group| {
+                    let rounded = day.rounding().and_then(|rounding| group.rounded(rounding));
+                    (group.description().to_owned(), (group.delta(), rounded))
+                })
+            })
+            .into_group_map()
+            .into_iter()
+            .map(|(description, deltas)| {
+                let delta = deltas.iter().map(|(delta, _)| *delta).sum();
+                let rounded = deltas.iter().map(|(_, rounded)| *rounded).sum();
+                Total::new(description, delta).with_rounded(rounded)
+            })
+            .collect();
+
+        totals.sort_by(|left, right| {
+            right
+                .delta()
+                .cmp(&left.delta())
+                .then_with(|| left.name().cmp(right.name()))
+        });
+
+        totals
+    }
+}
+
+impl Total {
+    pub fn new(name: String, delta: TimeDelta) -> Self {
+        Self {
+            name,
+            delta,
+            rounded: None,
+        }
+    }
+
+    pub fn with_rounded(mut self, rounded: Option<TimeDelta>) -> Self {
+        self.rounded = rounded;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn delta(&self) -> TimeDelta {
+        self.delta
+    }
+
+    pub fn rounded(&self) -> Option<TimeDelta> {
+        self.rounded
+    }
+}
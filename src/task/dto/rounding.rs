@@ -0,0 +1,115 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::TimeDelta;
+use eyre::{eyre, Context};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rounding {
+    mode: RoundingMode,
+    minutes: u32,
+    #[serde(default)]
+    per: RoundingScope,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingMode {
+    Nearest,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingScope {
+    #[default]
+    Task,
+    Group,
+    Day,
+}
+
+impl Rounding {
+    pub fn per(&self) -> RoundingScope {
+        self.per
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.minutes == 0
+    }
+
+    pub fn round(&self, delta: TimeDelta) -> TimeDelta {
+        if self.is_exact() {
+            return delta;
+        }
+        let step = i64::from(self.minutes) * 60;
+        let seconds = delta.num_seconds();
+        let down = seconds - seconds.rem_euclid(step);
+        let up = if down == seconds { down } else { down + step };
+        let rounded = match self.mode {
+            RoundingMode::Down => down,
+            RoundingMode::Up => up,
+            RoundingMode::Nearest if seconds - down >= up - seconds => up,
+            RoundingMode::Nearest => down,
+        };
+        TimeDelta::seconds(rounded)
+    }
+}
+
+impl Display for Rounding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_exact() {
+            return write!(f, "none");
+        }
+        let mode = match self.mode {
+            RoundingMode::Nearest => "nearest",
+            RoundingMode::Up => "up",
+            RoundingMode::Down => "down",
+        };
+        let per = match self.per {
+            RoundingScope::Task => "task",
+            RoundingScope::Group => "group",
+            RoundingScope::Day => "day",
+        };
+        write!(f, "{}:{}:{}", mode, self.minutes, per)
+    }
+}
+
+impl FromStr for Rounding {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        let value = s.trim().to_ascii_lowercase();
+        if value == "none" {
+            return Ok(Self {
+                mode: RoundingMode::Nearest,
+                minutes: 0,
+                per: RoundingScope::Task,
+            });
+        }
+        let mut parts = value.split(':');
+        let mode = match parts.next() {
+            Some("nearest") => RoundingMode::Nearest,
+            Some("up") => RoundingMode::Up,
+            Some("down") => RoundingMode::Down,
+            _ => return Err(eyre!("could not convert string to rounding: {}", s)),
+        };
+        let minutes = parts
+            .next()
+            .ok_or_else(|| eyre!("the rounding needs the minutes: {}", s))?
+            .parse()
+            .wrap_err("could not parse the minutes of the rounding")
+            .with_context(|| s.to_owned())?;
+        let per = match parts.next() {
+            None | Some("task") => RoundingScope::Task,
+            Some("group") => RoundingScope::Group,
+            Some("day") => RoundingScope::Day,
+            Some(per) => return Err(eyre!("could not convert string to rounding scope: {}", per)),
+        };
+        if parts.next().is_some() {
+            return Err(eyre!("could not convert string to rounding: {}", s));
+        }
+        Ok(Self { mode, minutes, per })
+    }
+}
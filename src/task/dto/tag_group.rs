@@ -0,0 +1,28 @@
+use chrono::TimeDelta;
+
+use crate::day::Day;
+
+use super::Task;
+
+pub struct TagGroup {
+    tag: String,
+    tasks: Vec<Task<Day>>,
+}
+
+impl TagGroup {
+    pub fn new(tag: String, tasks: Vec<Task<Day>>) -> Self {
+        Self { tag, tasks }
+    }
+
+    pub fn tag(&self) -> &str {
+        self.tag.as_str()
+    }
+
+    pub fn tasks(&self) -> impl Iterator<Item = &Task<Day>> {
+        self.tasks.iter()
+    }
+
+    pub fn delta(&self) -> TimeDelta {
+        self.tasks.iter().map(|task| task.delta()).sum()
+    }
+}
@@ -2,7 +2,7 @@ use chrono::{NaiveTime, TimeDelta};
 
 use crate::day::Day;
 
-use super::Task;
+use super::{Rounding, RoundingScope, Task};
 
 pub struct TaskGroup {
     description: String,
@@ -40,6 +40,19 @@ impl TaskGroup {
         self.tasks.iter().map(|task| task.delta()).sum()
     }
 
+    pub fn rounded(&self, rounding: &Rounding) -> Option<TimeDelta> {
+        match rounding.per() {
+            RoundingScope::Task => Some(
+                self.tasks
+                    .iter()
+                    .map(|task| rounding.round(task.delta()))
+                    .sum(),
+            ),
+            RoundingScope::Group => Some(rounding.round(self.delta())),
+            RoundingScope::Day => None,
+        }
+    }
+
     pub fn latest_time(&self) -> Option<NaiveTime> {
         self.tasks.iter().fold(None, |latest_time, task| {
             let task_end = task.end_or_day_time();
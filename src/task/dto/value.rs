@@ -2,11 +2,11 @@ use std::fmt::Display;
 
 use chrono::{Local, NaiveTime, TimeDelta};
 use rusqlite::Row;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::day::{Day, DayRef, DayReference};
+use crate::day::{Day, DayRef, DayReference, END_OF_DAY};
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task<DayRefImpl> {
     id: u64,
     #[serde(skip)]
@@ -14,6 +14,8 @@ pub struct Task<DayRefImpl> {
     start: NaiveTime,
     end: Option<NaiveTime>,
     description: String,
+    tags: Vec<String>,
+    billable: bool,
 }
 
 pub struct MutTask {}
@@ -27,6 +29,18 @@ impl<DayRefImpl> Task<DayRefImpl> {
         self.description.as_str()
     }
 
+    pub fn tags(&self) -> &[String] {
+        self.tags.as_slice()
+    }
+
+    pub fn is_billable(&self) -> bool {
+        self.billable
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|value| value == tag)
+    }
+
     pub fn start(&self) -> NaiveTime {
         self.start
     }
@@ -50,6 +64,7 @@ where
         start: NaiveTime,
         end: Option<NaiveTime>,
         mut description: String,
+        tags: Vec<String>,
     ) -> Self {
         description.make_ascii_lowercase();
         Self {
@@ -58,9 +73,16 @@ where
             start,
             end,
             description,
+            tags: normalize_tags(tags),
+            billable: true,
         }
     }
 
+    pub fn with_billable(mut self, billable: bool) -> Self {
+        self.billable = billable;
+        self
+    }
+
     pub fn day_id(&self) -> u64 {
         self.day.id()
     }
@@ -72,7 +94,7 @@ impl Task<u64> {
     }
 
     pub fn delta(&self) -> Option<TimeDelta> {
-        self.end.map(|end| end - self.start)
+        self.end.map(|end| span(self.start, end))
     }
 }
 
@@ -86,7 +108,7 @@ impl Task<Day> {
     }
 
     pub fn delta(&self) -> TimeDelta {
-        self.end_or_day_time() - self.start
+        span(self.start, self.end_or_day_time())
     }
 }
 
@@ -99,16 +121,32 @@ impl<DayRefImpl> Display for Task<DayRefImpl> {
             self.id,
             self.start.format("%H:%M")
         )?;
-        let Some(end) = self.end else {
-            return write!(f, "...");
+        match self.end {
+            Some(END_OF_DAY) => write!(f, "24:00")?,
+            Some(end) => write!(f, "{}", end.format("%H:%M"))?,
+            None => write!(f, "...")?,
         };
-        write!(f, "{}", end.format("%H:%M"))
+        if !self.tags.is_empty() {
+            write!(f, " tags={}", self.tags.join(","))?;
+        }
+        if !self.billable {
+            write!(f, " billable=false")?;
+        }
+        Ok(())
     }
 }
 
 impl MutTask {
     pub(crate) fn with_day(task: Task<u64>, day: Day) -> Task<Day> {
-        Task::new(task.id, day, task.start, task.end, task.description)
+        Task::new(
+            task.id,
+            day,
+            task.start,
+            task.end,
+            task.description,
+            task.tags,
+        )
+        .with_billable(task.billable)
     }
 
     pub(crate) fn set_description<DayRefImpl>(task: &mut Task<DayRefImpl>, description: &str) {
@@ -116,6 +154,14 @@ impl MutTask {
         task.description.push_str(description);
     }
 
+    pub(crate) fn set_tags<DayRefImpl>(task: &mut Task<DayRefImpl>, tags: Vec<String>) {
+        task.tags = normalize_tags(tags);
+    }
+
+    pub(crate) fn set_billable<DayRefImpl>(task: &mut Task<DayRefImpl>, billable: bool) {
+        task.billable = billable;
+    }
+
     pub(crate) fn set_start(task: &mut Task<impl DayRef>, time: NaiveTime) {
         task.start = time;
     }
@@ -124,3 +170,21 @@ impl MutTask {
         task.end = Some(time);
     }
 }
+
+fn span(start: NaiveTime, end: NaiveTime) -> TimeDelta {
+    if end == END_OF_DAY {
+        return end - start + TimeDelta::nanoseconds(1);
+    }
+    end - start
+}
+
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut tags: Vec<_> = tags
+        .into_iter()
+        .map(|tag| tag.trim().to_ascii_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
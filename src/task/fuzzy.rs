@@ -0,0 +1,36 @@
+use eyre::eyre;
+
+pub fn fuzzy_match<'a>(query: &str, descriptions: &'a [String]) -> eyre::Result<&'a str> {
+    let query = normalize(query);
+    if query.is_empty() {
+        return Err(eyre!("the description to match is empty"));
+    }
+    let normalized: Vec<_> = descriptions
+        .iter()
+        .map(|description| (description.as_str(), normalize(description)))
+        .collect();
+    if let Some((description, _)) = normalized.iter().find(|(_, value)| *value == query) {
+        return Ok(description);
+    }
+    let matches: Vec<_> = normalized
+        .iter()
+        .filter(|(_, value)| value.contains(&query))
+        .map(|(description, _)| *description)
+        .collect();
+    match matches.as_slice() {
+        [] => Err(eyre!("no task matches the description: {}", query)),
+        [description] => Ok(description),
+        _ => Err(eyre!(
+            "the description is ambiguous, it matches: {}",
+            matches.join(", ")
+        )),
+    }
+}
+
+fn normalize(description: &str) -> String {
+    description
+        .chars()
+        .filter(|char| char.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
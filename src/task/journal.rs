@@ -0,0 +1,191 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use chrono::{Local, NaiveDateTime, NaiveTime};
+use eyre::Context;
+use rusqlite::{Connection, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::day::DayRef;
+
+use super::Task;
+
+const JOURNAL_LIMIT: u64 = 100;
+
+pub struct Journal {
+    connection: Rc<Connection>,
+    name: RefCell<String>,
+    operation: Cell<Option<u64>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Operation {
+    id: u64,
+    name: String,
+    created: NaiveDateTime,
+}
+
+pub struct Entry {
+    pub task_id: u64,
+    pub before: Option<Task<u64>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    day_id: u64,
+    start: NaiveTime,
+    end: Option<NaiveTime>,
+    description: String,
+    tags: Vec<String>,
+    #[serde(default = "billable")]
+    billable: bool,
+}
+
+impl Journal {
+    pub fn new(connection: Rc<Connection>) -> Self {
+        Self {
+            connection,
+            name: RefCell::new("change".to_owned()),
+            operation: Cell::new(None),
+        }
+    }
+
+    pub fn begin(&self, name: &str) {
+        self.name.replace(name.to_owned());
+        self.operation.set(None);
+    }
+
+    fn open(&self) -> eyre::Result<u64> {
+        let name = self.name.borrow();
+        let now = Local::now().naive_local();
+        self.connection
+            .execute(
+                "INSERT INTO operations (name, created) VALUES (?1, ?2)",
+                (name.as_str(), now),
+            )
+            .wrap_err("could not record the operation")
+            .with_context(|| name.clone())?;
+        let id = self.connection.last_insert_rowid() as u64;
+        self.operation.set(Some(id));
+        self.connection.execute(
+            "DELETE FROM journal WHERE operation_id <= ?1",
+            (id.saturating_sub(JOURNAL_LIMIT),),
+        )?;
+        self.connection.execute(
+            "DELETE FROM operations WHERE id <= ?1",
+            (id.saturating_sub(JOURNAL_LIMIT),),
+        )?;
+        Ok(id)
+    }
+
+    pub fn record(
+        &self,
+        task_id: u64,
+        before: Option<&Task<impl DayRef>>,
+        after: Option<&Task<impl DayRef>>,
+    ) -> eyre::Result<()> {
+        let operation = match self.operation.get() {
+            Some(operation) => operation,
+            None => self.open()?,
+        };
+        let before = before.map(snapshot).transpose()?;
+        let after = after.map(snapshot).transpose()?;
+        self.connection
+            .execute(
+                "INSERT INTO journal (operation_id, task_id, before, after)
+                 VALUES (?1, ?2, ?3, ?4)",
+                (operation, task_id, before, after),
+            )
+            .wrap_err("could not record the change in the journal")?;
+        Ok(())
+    }
+
+    pub fn operations(&self, count: usize) -> eyre::Result<Vec<Operation>> {
+        self.connection
+            .prepare(
+                "SELECT id, name, created FROM operations
+                 WHERE id IN (SELECT operation_id FROM journal)
+                 ORDER BY id DESC
+                 LIMIT ?1",
+            )?
+            .query_map((count,), operation_from_row)
+            .wrap_err("could not query the journal")?
+            .collect::<Result<_, _>>()
+            .wrap_err("cannot convert operations from sql statement")
+    }
+
+    pub fn entries(&self, operation: &Operation) -> eyre::Result<Vec<Entry>> {
+        self.connection
+            .prepare("SELECT task_id, before FROM journal WHERE operation_id=?1 ORDER BY id DESC")?
+            .query_map((operation.id,), entry_from_row)
+            .wrap_err("could not query the journal")?
+            .collect::<Result<_, _>>()
+            .wrap_err("cannot convert journal entries from sql statement")
+    }
+
+    pub fn remove(&self, operation: &Operation) -> eyre::Result<()> {
+        self.connection
+            .execute("DELETE FROM journal WHERE operation_id=?1", (operation.id,))?;
+        self.connection
+            .execute("DELETE FROM operations WHERE id=?1", (operation.id,))?;
+        Ok(())
+    }
+}
+
+impl Operation {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn created(&self) -> NaiveDateTime {
+        self.created
+    }
+}
+
+fn snapshot(task: &Task<impl DayRef>) -> eyre::Result<String> {
+    let snapshot = Snapshot {
+        day_id: task.day_id(),
+        start: task.start(),
+        end: task.end(),
+        description: task.description().to_owned(),
+        tags: task.tags().to_vec(),
+        billable: task.is_billable(),
+    };
+    serde_json::to_string(&snapshot).wrap_err("could not serialize the task for the journal")
+}
+
+fn operation_from_row(row: &Row) -> rusqlite::Result<Operation> {
+    let id = row.get("id")?;
+    let name = row.get("name")?;
+    let created = row.get("created")?;
+    Ok(Operation { id, name, created })
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<Entry> {
+    let task_id = row.get("task_id")?;
+    let before: Option<String> = row.get("before")?;
+    let before = before
+        .map(|before| serde_json::from_str::<Snapshot>(&before))
+        .transpose()
+        .map_err(|error| {
+            rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, error.into())
+        })?
+        .map(|snapshot| {
+            Task::new(
+                task_id,
+                snapshot.day_id,
+                snapshot.start,
+                snapshot.end,
+                snapshot.description,
+                snapshot.tags,
+            )
+            .with_billable(snapshot.billable)
+        });
+    Ok(Entry { task_id, before })
+}
+
+fn billable() -> bool {
+    true
+}
@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use chrono::{NaiveTime, TimeDelta};
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveTime, TimeDelta, Weekday};
 use eyre::{eyre, ContextCompat};
 
 pub enum TimeOrDelta {
@@ -8,6 +8,15 @@ pub enum TimeOrDelta {
     Delta(TimeDelta),
 }
 
+impl TimeOrDelta {
+    pub fn resolve(&self, time: NaiveTime) -> NaiveTime {
+        match self {
+            Self::Time(time) => *time,
+            Self::Delta(delta) => time + *delta,
+        }
+    }
+}
+
 impl FromStr for TimeOrDelta {
     type Err = eyre::Error;
 
@@ -19,13 +28,13 @@ impl FromStr for TimeOrDelta {
                 .wrap_err("time values are not right")?;
             return Ok(Self::Time(time));
         };
-        if s[..1] == *"+" {
-            let minutes = u32::from_str(&s[1..])?;
+        if let Some(minutes) = s.strip_prefix('+') {
+            let minutes = u32::from_str(minutes)?;
             let delta = TimeDelta::minutes(minutes as i64);
             return Ok(Self::Delta(delta));
         }
-        if s[..1] == *"-" {
-            let minutes = u32::from_str(&s[1..])?;
+        if let Some(minutes) = s.strip_prefix('-') {
+            let minutes = u32::from_str(minutes)?;
             let delta = TimeDelta::minutes(-(minutes as i64));
             return Ok(Self::Delta(delta));
         }
@@ -33,6 +42,135 @@ impl FromStr for TimeOrDelta {
     }
 }
 
+pub enum DateExpression {
+    Date(NaiveDate),
+    Offset(i64),
+    Weekday(Weekday),
+    LastWeekday(Weekday),
+}
+
+pub struct DateRange {
+    from: DateExpression,
+    to: DateExpression,
+}
+
+impl DateExpression {
+    pub fn resolve(&self, today: NaiveDate) -> eyre::Result<NaiveDate> {
+        match self {
+            Self::Date(date) => Ok(*date),
+            Self::Offset(days) => add_days(today, *days),
+            Self::Weekday(weekday) => {
+                let days = today.weekday().days_since(*weekday);
+                add_days(today, -(days as i64))
+            }
+            Self::LastWeekday(weekday) => {
+                let days = match today.weekday().days_since(*weekday) {
+                    0 => 7,
+                    days => days,
+                };
+                add_days(today, -(days as i64))
+            }
+        }
+    }
+}
+
+impl FromStr for DateExpression {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        let s = s.trim().to_ascii_lowercase();
+        match s.as_str() {
+            "today" => return Ok(Self::Offset(0)),
+            "yesterday" => return Ok(Self::Offset(-1)),
+            "tomorrow" => return Ok(Self::Offset(1)),
+            _ => {}
+        }
+        if let Ok(days) = i64::from_str(&s) {
+            return Ok(Self::Offset(days));
+        }
+        if let Some(weekday) = s.strip_prefix("last ") {
+            let weekday = Weekday::from_str(weekday.trim())
+                .map_err(|_| eyre!("could not convert string to weekday: {}", weekday))?;
+            return Ok(Self::LastWeekday(weekday));
+        }
+        if let Ok(weekday) = Weekday::from_str(&s) {
+            return Ok(Self::Weekday(weekday));
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(&s, "%Y-%m-%d") {
+            return Ok(Self::Date(date));
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(&s, "%y-%m-%d") {
+            return Ok(Self::Date(date));
+        }
+        Err(eyre!("could not convert string to date: {}", s))
+    }
+}
+
+impl DateRange {
+    pub fn resolve(&self, today: NaiveDate) -> eyre::Result<(NaiveDate, NaiveDate)> {
+        let from = self.from.resolve(today)?;
+        let to = self.to.resolve(today)?;
+        if from > to {
+            return Err(eyre!("the range starts after it ends: {} > {}", from, to));
+        }
+        Ok((from, to))
+    }
+}
+
+impl FromStr for DateRange {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        let Some((from, to)) = s.split_once("..") else {
+            let from = DateExpression::from_str(s)?;
+            let to = DateExpression::from_str(s)?;
+            return Ok(Self { from, to });
+        };
+        let from = DateExpression::from_str(from)?;
+        let to = match to.trim() {
+            "" => DateExpression::Offset(0),
+            to => DateExpression::from_str(to)?,
+        };
+        Ok(Self { from, to })
+    }
+}
+
+pub fn month(date: NaiveDate, offset: i32) -> eyre::Result<(NaiveDate, NaiveDate)> {
+    let first = date
+        .with_day(1)
+        .wrap_err("could not get the first day of the month")?;
+    let months = Months::new(offset.unsigned_abs());
+    let from = if offset < 0 {
+        first.checked_sub_months(months)
+    } else {
+        first.checked_add_months(months)
+    }
+    .wrap_err_with(|| format!("the month is out of range: {} {:+} months", date, offset))?;
+    let to = from
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .wrap_err("could not get the last day of the month")?;
+    Ok((from, to))
+}
+
+pub fn year(date: NaiveDate, offset: i32) -> eyre::Result<(NaiveDate, NaiveDate)> {
+    let year = date.year() + offset;
+    let from = NaiveDate::from_ymd_opt(year, 1, 1);
+    let to = NaiveDate::from_ymd_opt(year, 12, 31);
+    from.zip(to)
+        .wrap_err_with(|| format!("the year is out of range: {}", year))
+}
+
+fn add_days(date: NaiveDate, days: i64) -> eyre::Result<NaiveDate> {
+    let delta = Days::new(days.unsigned_abs());
+    if days < 0 {
+        date.checked_sub_days(delta)
+    } else {
+        date.checked_add_days(delta)
+    }
+    .wrap_err_with(|| format!("the date is out of range: {} {:+} days", date, days))
+}
+
 fn is_digit(s: &str) -> bool {
-    s.chars().all(|char| matches!(char, '0'..='9'))
+    s.chars().all(|char| char.is_ascii_digit())
 }